@@ -1,13 +1,22 @@
-use models::config::{parse_set_operations, Config, OPERATIONS};
-use services::server::{get_existing_sockets, send_message_socket, spawn_server};
+use models::config::{
+    get_config_value, get_operation, parse_config_operations, parse_set_operations, Config,
+    BAR_OPERATION, DAEMON_OPERATION, OPERATIONS, SAVE_OPERATION, STATUS_OPERATION, WATCH_OPERATION,
+};
+use models::message::Message;
+use models::output::Output;
+use services::bar;
+use services::server::{
+    bind_socket, daemon_socket, free_socket, get_existing_sockets, is_reachable,
+    send_message_socket, sort_sockets, spawn_detached, spawn_server, watch_socket,
+};
 use signal_hook::{
     consts::{SIGHUP, SIGINT, SIGTERM},
     iterator::Signals,
 };
-use std::{env, thread};
+use std::{env, io, thread};
 use utils::consts::{
-    BREAK_ICON, LONG_BREAK_TIME, MAX_ITERATIONS, MINUTE, PAUSE_ICON, PLAY_ICON, SHORT_BREAK_TIME,
-    WORK_ICON, WORK_TIME,
+    BREAK_COLOR, BREAK_ICON, FORMAT, LONG_BREAK_TIME, MAX_ITERATIONS, MINUTE, PAUSE_COLOR,
+    PAUSE_ICON, PLAY_ICON, SHORT_BREAK_TIME, WORK_COLOR, WORK_ICON, WORK_TIME,
 };
 
 mod models;
@@ -27,29 +36,102 @@ fn main() -> std::io::Result<()> {
         return Ok(());
     }
 
-    let config = Config::from_options(options);
+    // only a format given on the command line replaces the one of the running instance
+    let format = get_config_value(&options, vec!["-f", "--format"])
+        .filter(|format| !format.is_empty())
+        .cloned();
+    // passed on to a server started in the background
+    let config_file = get_config_value(&options, vec!["-c", "--config"]).cloned();
+    // an option's value, e.g. --work-icon bar, is never taken for one of the modes below
+    let mode = get_operation(&options).map(str::to_string);
+    let mut config = Config::from_options(options);
 
     let mut sockets = get_existing_sockets(&config.binary_name);
-    let socket_path: String = format!(
-        "{}/{}{}.socket",
-        env::temp_dir().display(),
-        config.binary_name,
-        sockets.len(),
-    );
 
     let operation = env::args()
         .filter(|x| OPERATIONS.contains(&x.as_str()))
         .collect::<Vec<String>>();
 
     let set_operation = parse_set_operations(env::args().collect::<Vec<String>>());
+    let config_operation = parse_config_operations(&env::args().collect::<Vec<String>>());
+
+    if mode.as_deref() == Some(STATUS_OPERATION) {
+        let message = match format {
+            Some(format) => Message::new(STATUS_OPERATION, &format).encode(),
+            None => STATUS_OPERATION.to_string(),
+        };
+        // every instance has the same state, the lowest one that's still running answers
+        sort_sockets(&mut sockets);
+        for socket in &sockets {
+            if let Ok(reply) = send_message_socket(socket, &message) {
+                if !reply.is_empty() {
+                    println!("{reply}");
+                    return Ok(());
+                }
+            }
+        }
+        // nothing is printed, so prompts can leave it out
+        std::process::exit(1);
+    }
+
+    if mode.as_deref() == Some(WATCH_OPERATION) {
+        // every instance has the same state, the lowest one that's still running is followed
+        sort_sockets(&mut sockets);
+        let Some(socket) = sockets.iter().find(|socket| is_reachable(socket)) else {
+            println!("err: no running instance to watch");
+            std::process::exit(1);
+        };
+        if let Err(e) = watch_socket(socket, &mut io::stdout()) {
+            println!("err: unable to watch {socket}, err == {e}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if mode.as_deref() == Some(BAR_OPERATION) {
+        // waybar sends these to its modules, see process_signals
+        let _dont_handle = Signals::new((34..64).collect::<Vec<i32>>()).unwrap();
+        bar::run(config, config_file.as_deref());
+        return Ok(());
+    }
+
+    // the daemon has a socket of its own, so bars always know where to find it
+    if mode.as_deref() == Some(DAEMON_OPERATION) {
+        let socket_path = daemon_socket(&config.binary_name);
+        let listener = match bind_socket(&socket_path) {
+            Ok(listener) => listener,
+            Err(e) if e.kind() == io::ErrorKind::AddrInUse => {
+                println!("err: a daemon is already running");
+                std::process::exit(1);
+            }
+            Err(e) => return Err(e),
+        };
+        // printing is left to the bars connected to it
+        config.output = Output::None;
+        process_signals(socket_path.clone());
+        spawn_server(listener, &socket_path, config);
+        return Ok(());
+    }
 
-    if operation.is_empty() && set_operation.is_empty() {
-        sockets.push(socket_path.clone());
+    if operation.is_empty() && set_operation.is_empty() && config_operation.is_empty() {
+        let (socket_path, listener) = free_socket(&config.binary_name)?;
         process_signals(socket_path.clone());
-        spawn_server(&socket_path, config);
+        spawn_server(listener, &socket_path, config);
         return Ok(());
     }
 
+    // keyboard shortcuts keep working while the bar is restarted or not there at all
+    if config.start_server && !sockets.iter().any(|socket| is_reachable(socket)) {
+        match spawn_detached(&config.binary_name, config_file.as_deref()) {
+            Ok(socket_path) => sockets = vec![socket_path],
+            Err(e) => {
+                println!("err: unable to start the server, err == {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let mut failed = false;
     for socket in sockets {
         if !operation.is_empty() {
             match send_message_socket(&socket, &operation[0]) {
@@ -59,10 +141,40 @@ fn main() -> std::io::Result<()> {
         }
         for msg in &set_operation {
             match send_message_socket(&socket, &msg.encode()) {
+                Ok(reply) if !reply.is_empty() => {
+                    println!("{reply}");
+                    failed |= reply.starts_with("err:");
+                }
+                Ok(_) => {}
+                Err(_) => println!("warn: failed to connect to {}", socket),
+            };
+        }
+        for msg in &config_operation {
+            // only write to the config file if the value was accepted
+            if msg.name() == SAVE_OPERATION && failed {
+                break;
+            }
+            match send_message_socket(&socket, &msg.encode()) {
+                Ok(reply) if !reply.is_empty() => {
+                    println!("{reply}");
+                    failed |= reply.starts_with("err:");
+                }
                 Ok(_) => {}
                 Err(_) => println!("warn: failed to connect to {}", socket),
             };
         }
+        // every instance would give the same answer
+        if config_operation
+            .first()
+            .is_some_and(|msg| msg.name() == "get")
+            && !failed
+        {
+            break;
+        }
+    }
+
+    if failed {
+        std::process::exit(1);
     }
     Ok(())
 }
@@ -91,32 +203,77 @@ fn print_help() {
     options:
         -h, --help                  Prints this help message
         -v, --version               Prints the version string
-        -w, --work <value>          Sets how long a work cycle is, in minutes. default: {}
-        -s, --shortbreak <value>    Sets how long a short break is, in minutes. default: {}
-        -l, --longbreak <value>     Sets how long a long break is, in minutes. default: {}
+        -c, --config <path>         Sets the config file to use. default: ~/.config/waybar-module-pomodoro/config.toml
+        -w, --work <value>          Sets how long a work cycle is. default: {}m
+        -s, --shortbreak <value>    Sets how long a short break is. default: {}m
+        -l, --longbreak <value>     Sets how long a long break is. default: {}m
         -i, --intervals <value>     How many intervals there should be before a long break. default: {}
 
         -p, --play <value>          Sets custom play icon/text. default: {}
         -a, --pause <value>         Sets custom pause icon/text. default: {}
         -o, --work-icon <value>     Sets custom work icon/text. default: {}
         -b, --break-icon <value>    Sets custom break icon/text. default: {}
+        -f, --format <value>        Sets the text shown in the bar. default: {}
 
         --no-icons                  Disable the pause/play icon
         --no-work-icons             Disable the work/break icon
 
+        --output <value>            The bar to print for: waybar, i3bar (also swaybar), polybar, lemonbar, plain or none. default: waybar
+        --work-color <value>        Sets the color of a work cycle in i3bar, polybar and lemonbar. default: {}
+        --break-color <value>       Sets the color of a break in i3bar, polybar and lemonbar. default: {}
+        --pause-color <value>       Sets the color of a paused timer in i3bar, polybar and lemonbar. default: {}
+        --start-server              Starts the timer in the background when an operation finds none running
+
         --autow                     Starts a work cycle automatically after a break
-        --autob                     Starts a break cycle automatically after work
-        --persist                   Persist timer state between sessions
+        --autob                     Starts a break cycle automatically after work, same as --auto-short --auto-long
+        --auto-short                Starts a short break automatically after work
+        --auto-long                 Starts a long break automatically after work
+        --auto-start-delay <value>  Waits this long before an automatically started cycle begins. default: 0s
+        --warning <value>           Warns this long before any cycle ends, with a notification and a warning class
+        --live-notification         Keep a notification around that shows the remaining time
+        --persist                   Persist timer state and the active profile between sessions
+        --history                   Record finished and skipped cycles to a history file
+        --flowtime                  Count work up with no end, stopping it starts a break earned from the work time
+        --flowtime-ratio <value>    How much of the work time is earned as break in flowtime mode. default: 1/5
+        --overtime                  Keep counting a finished cycle until acknowledged, instead of moving on and stopping
+        --overtime-break            Add any overtime worked to the following break
+        --on-work-start <cmd>       Runs a shell command when a work cycle starts
+        --on-break-start <cmd>      Runs a shell command when a short break starts
+        --on-long-break-start <cmd> Runs a shell command when a long break starts
+        --on-pause <cmd>            Runs a shell command when the timer is paused
+        --on-resume <cmd>           Runs a shell command when the timer is resumed
+        --on-reset <cmd>            Runs a shell command when the timer is reset
+        --on-complete <cmd>         Runs a shell command when a cycle runs out
+        --pause-media <value>       Pauses media players during breaks or work, then plays them again: off, break or work
+        --media-players <value>     Only pause these players, e.g. spotify,mpv
+        --pause-on-lock             Pauses the timer while the session is locked or idle
+        --resume-on-unlock          Resumes a timer paused by --pause-on-lock once the session is back
+        --idle-reset <value>        Starts work over after being away this long. default: 0s, off
 
     operations:
+        daemon                      Run the timer without printing anything, e.g. as a systemd user service
+        bar                         Show the timer of a running daemon, following it across restarts of either
+
         toggle                      Toggles the timer
         start                       Start the timer
         stop                        Stop the timer
         reset                       Reset timer to initial state
+        skip                        Skip to the next cycle
+        break                       End the current work cycle and start a break
 
         set-work <value>            Set new work time
         set-short <value>           Set new short break time
-        set-long <value>            Set new long break time"#,
+        set-long <value>            Set new long break time
+        add <value>                 Add time to the current cycle only
+        sub <value>                 Remove time from the current cycle only
+        profile <name>              Switch to a profile from the config file
+
+        set <key> <value> [--save]  Change an option at runtime, --save also writes it to the config file
+        get <key>                   Print the current value of an option
+        status [--format <value>]   Print the text of the running module once, or nothing if there is none
+        watch                       Print the state as a line of JSON on every change, until the module exits
+
+    durations can be given as e.g. 25m, 1h30m, 90s or 0.5m, up to 168h. plain numbers are read as minutes"#,
         WORK_TIME / MINUTE,
         SHORT_BREAK_TIME / MINUTE,
         LONG_BREAK_TIME / MINUTE,
@@ -125,5 +282,9 @@ fn print_help() {
         PAUSE_ICON,
         WORK_ICON,
         BREAK_ICON,
+        FORMAT,
+        WORK_COLOR,
+        BREAK_COLOR,
+        PAUSE_COLOR,
     );
 }
@@ -1,15 +1,165 @@
+use std::{collections::BTreeMap, fmt, path::PathBuf};
+
 use crate::{
-    models::message::Message, utils::consts::MAX_ITERATIONS, BREAK_ICON, LONG_BREAK_TIME, MINUTE,
-    PAUSE_ICON, PLAY_ICON, SHORT_BREAK_TIME, WORK_ICON, WORK_TIME,
+    models::message::Message,
+    utils::{
+        consts::{
+            BREAK_COLOR, FLOWTIME_RATIO, FORMAT, HOOK_TIMEOUT, MAX_ITERATIONS, PAUSE_COLOR,
+            WORK_COLOR,
+        },
+        helper::{format_duration, parse_delay, parse_ratio, parse_time},
+    },
+    BREAK_ICON, LONG_BREAK_TIME, PAUSE_ICON, PLAY_ICON, SHORT_BREAK_TIME, WORK_ICON, WORK_TIME,
+};
+
+use super::{
+    config_file::{self, ConfigFile},
+    media::{parse_players, PauseMedia},
+    notification::{format_urgency, parse_urgency, NotificationConfig},
+    output::Output,
+    phase::Phase,
+    profile::Profile,
+    sequence::{format_sequence, parse_sequence, Step},
 };
 
-pub const OPERATIONS: [&str; 4] = ["toggle", "start", "stop", "reset"];
+pub const OPERATIONS: [&str; 6] = ["toggle", "start", "stop", "reset", "skip", "break"];
 pub const SET_OPERATIONS: [&str; 3] = ["set-work", "set-short", "set-long"];
+pub const ADJUST_OPERATIONS: [&str; 2] = ["add", "sub"];
+pub const PROFILE_OPERATION: &str = "profile";
+pub const CONFIG_OPERATIONS: [&str; 2] = ["set", "get"];
+// sent after a successful set when --save is passed, writes the option to the config file
+pub const SAVE_OPERATION: &str = "save";
+// keeps the connection open, the server sends its state on every change
+pub const WATCH_OPERATION: &str = "watch";
+// replies with the text of the module, or the format given with it
+pub const STATUS_OPERATION: &str = "status";
+// runs the server without printing anything, bars connect to it with the bar operation
+pub const DAEMON_OPERATION: &str = "daemon";
+// prints what the server sends, the timer itself keeps running when the bar restarts
+pub const BAR_OPERATION: &str = "bar";
+
+// options followed by a value, see Config::from_options
+const VALUE_OPTIONS: [&str; 37] = [
+    "-c",
+    "--config",
+    "-w",
+    "--work",
+    "-s",
+    "--shortbreak",
+    "-l",
+    "--longbreak",
+    "-i",
+    "--intervals",
+    "-p",
+    "--play",
+    "-a",
+    "--pause",
+    "-o",
+    "--work-icon",
+    "-b",
+    "--break-icon",
+    "-f",
+    "--format",
+    "--output",
+    "--work-color",
+    "--break-color",
+    "--pause-color",
+    "--auto-start-delay",
+    "--warning",
+    "--flowtime-ratio",
+    "--on-work-start",
+    "--on-break-start",
+    "--on-long-break-start",
+    "--on-pause",
+    "--on-resume",
+    "--on-reset",
+    "--on-complete",
+    "--pause-media",
+    "--media-players",
+    "--idle-reset",
+];
+
+// every option that can be read with get, all but the last four can also be changed with set
+pub const KEYS: [&str; 47] = [
+    "work_time",
+    "short_break",
+    "long_break",
+    "intervals",
+    "no_icons",
+    "no_work_icons",
+    "play_icon",
+    "pause_icon",
+    "work_icon",
+    "break_icon",
+    "work_color",
+    "break_color",
+    "pause_color",
+    "auto_work",
+    "auto_short_break",
+    "auto_long_break",
+    "auto_start_delay",
+    "work_warning",
+    "short_break_warning",
+    "long_break_warning",
+    "live_notification",
+    "on_work_start",
+    "on_break_start",
+    "on_long_break_start",
+    "on_pause",
+    "on_resume",
+    "on_reset",
+    "on_complete",
+    "hook_timeout",
+    "pause_media",
+    "media_players",
+    "pause_on_lock",
+    "resume_on_unlock",
+    "idle_reset",
+    "persist",
+    "history",
+    "flowtime",
+    "flowtime_ratio",
+    "overtime",
+    "overtime_break",
+    "sequence",
+    "format",
+    "profile",
+    "output",
+    "start_server",
+    "profiles",
+    "binary_name",
+];
+
+// settings of the notification of each phase, e.g. notifications.work.enabled for enabled in
+// [notifications.work] of the config file
+pub const NOTIFICATION_KEYS: [&str; 8] = [
+    "enabled", "summary", "body", "icon", "urgency", "timeout", "category", "sound",
+];
+
+#[derive(Debug, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Number(u64),
+    Text(String),
+    // written to the config file as an array
+    List(Vec<String>),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bool(val) => write!(f, "{val}"),
+            Value::Number(val) => write!(f, "{val}"),
+            Value::Text(val) => write!(f, "{val}"),
+            Value::List(val) => write!(f, "{}", val.join(", ")),
+        }
+    }
+}
 
 pub struct Config {
-    pub work_time: u16,
-    pub short_break: u16,
-    pub long_break: u16,
+    pub work_time: u64,
+    pub short_break: u64,
+    pub long_break: u64,
     pub intervals: u8,
     pub no_icons: bool,
     pub no_work_icons: bool,
@@ -17,10 +167,65 @@ pub struct Config {
     pub pause_icon: String,
     pub work_icon: String,
     pub break_icon: String,
-    pub autow: bool,
-    pub autob: bool,
+    // colors of the text in i3bar, empty leaves it to the bar
+    pub work_color: String,
+    pub break_color: String,
+    pub pause_color: String,
+    // whether each phase starts by itself once the one before it is done
+    pub auto_work: bool,
+    pub auto_short_break: bool,
+    pub auto_long_break: bool,
+    // seconds to wait before an automatically started phase begins
+    pub auto_start_delay: u64,
+    // seconds before the end of each phase to warn at, 0 turns the warning off
+    pub work_warning: u64,
+    pub short_break_warning: u64,
+    pub long_break_warning: u64,
     pub persist: bool,
+    pub history: bool,
+    // work counts up with no end, and stopping it earns a break of flowtime_ratio times the work
+    pub flowtime: bool,
+    pub flowtime_ratio: f64,
+    // a finished cycle keeps counting until acknowledged instead of moving on and stopping
+    pub overtime: bool,
+    // overtime worked is added to the following break
+    pub overtime_break: bool,
+    // indexed by phase, like the times of the timer
+    pub notifications: [NotificationConfig; 3],
+    // keeps a single notification around that shows the same text as the bar
+    pub live_notification: bool,
+    // shell commands run when the timer changes, see services::hooks
+    pub on_work_start: Option<String>,
+    pub on_break_start: Option<String>,
+    pub on_long_break_start: Option<String>,
+    pub on_pause: Option<String>,
+    pub on_resume: Option<String>,
+    pub on_reset: Option<String>,
+    pub on_complete: Option<String>,
+    // seconds a hook may run before it's killed
+    pub hook_timeout: u64,
+    // pauses media players for breaks or for work, playing them again afterwards
+    pub pause_media: PauseMedia,
+    // names of the players to pause, all of them when empty
+    pub media_players: Vec<String>,
+    // pauses the timer while the session is locked or idle, and maybe resumes it afterwards
+    pub pause_on_lock: bool,
+    pub resume_on_unlock: bool,
+    // seconds away after which work starts over, 0 turns it off
+    pub idle_reset: u64,
+    // custom steps to cycle through instead of the usual work/short/long breaks
+    pub sequence: Vec<Step>,
+    pub format: String,
+    pub profiles: BTreeMap<String, Profile>,
+    // the active profile, if any
+    pub profile: Option<String>,
+    // where set --save writes to, either --config or the default path
+    pub config_file: Option<PathBuf>,
     pub binary_name: String,
+    // the bar being talked to, see services::output
+    pub output: Output,
+    // operations sent while no server is running start one in the background first
+    pub start_server: bool,
 }
 
 impl Default for Config {
@@ -36,92 +241,391 @@ impl Default for Config {
             pause_icon: PAUSE_ICON.to_string(),
             work_icon: WORK_ICON.to_string(),
             break_icon: BREAK_ICON.to_string(),
-            autow: Default::default(),
-            autob: Default::default(),
+            work_color: WORK_COLOR.to_string(),
+            break_color: BREAK_COLOR.to_string(),
+            pause_color: PAUSE_COLOR.to_string(),
+            auto_work: Default::default(),
+            auto_short_break: Default::default(),
+            auto_long_break: Default::default(),
+            auto_start_delay: Default::default(),
+            work_warning: Default::default(),
+            short_break_warning: Default::default(),
+            long_break_warning: Default::default(),
             persist: Default::default(),
+            history: Default::default(),
+            flowtime: Default::default(),
+            flowtime_ratio: FLOWTIME_RATIO,
+            overtime: Default::default(),
+            overtime_break: Default::default(),
+            notifications: Default::default(),
+            live_notification: Default::default(),
+            on_work_start: Default::default(),
+            on_break_start: Default::default(),
+            on_long_break_start: Default::default(),
+            on_pause: Default::default(),
+            on_resume: Default::default(),
+            on_reset: Default::default(),
+            on_complete: Default::default(),
+            hook_timeout: HOOK_TIMEOUT,
+            pause_media: Default::default(),
+            media_players: Default::default(),
+            pause_on_lock: Default::default(),
+            resume_on_unlock: Default::default(),
+            idle_reset: Default::default(),
+            sequence: Default::default(),
+            format: FORMAT.to_string(),
+            profiles: Default::default(),
+            profile: Default::default(),
+            config_file: Default::default(),
             binary_name: Default::default(),
+            output: Default::default(),
+            start_server: Default::default(),
         }
     }
 }
 
 impl Config {
     pub fn from_options(options: Vec<String>) -> Self {
-        let mut work_time: u16 = WORK_TIME;
-        let mut short_break: u16 = SHORT_BREAK_TIME;
-        let mut long_break: u16 = LONG_BREAK_TIME;
-        let mut intervals: u8 = MAX_ITERATIONS;
-        let mut no_icons = false;
-        let mut no_work_icons = false;
-        let mut play_icon = PLAY_ICON.to_string();
-        let mut pause_icon = PAUSE_ICON.to_string();
-        let mut work_icon = WORK_ICON.to_string();
-        let mut break_icon = BREAK_ICON.to_string();
-        let mut autow = false;
-        let mut autob = false;
-        let mut persist = false;
-
         let binary_path = options.first().unwrap();
         let binary_name = binary_path.split('/').next_back().unwrap().to_string();
 
+        let mut config = Self {
+            work_time: WORK_TIME,
+            short_break: SHORT_BREAK_TIME,
+            long_break: LONG_BREAK_TIME,
+            config_file: get_config_value(&options, vec!["-c", "--config"])
+                .map(PathBuf::from)
+                .or_else(config_file::default_path),
+            binary_name,
+            ..Default::default()
+        };
+
+        // the config file only provides defaults, options on the command line take precedence
+        let mut profiles = None;
+        if let Some(mut file) = load_config_file(&options) {
+            profiles = file.profiles.take();
+            file.apply(&mut config);
+        }
+
         for opt in options.iter() {
             match opt.as_str() {
                 "-w" | "--work" => {
                     let unparsed = get_config_value_except(&options, opt);
-                    match unparsed.parse::<u16>() {
-                        Ok(val) => work_time = val * MINUTE,
-                        Err(_) => println!("err: invalid value for {opt}. val == {unparsed}"),
+                    match parse_time(&unparsed) {
+                        Ok(val) => config.work_time = val,
+                        Err(e) => {
+                            eprintln!("err: invalid value for {opt}. val == {unparsed}, err == {e}")
+                        }
                     }
                 }
                 "-s" | "--shortbreak" => {
                     let unparsed = get_config_value_except(&options, opt);
-                    match unparsed.parse::<u16>() {
-                        Ok(val) => short_break = val * MINUTE,
-                        Err(_) => println!("err: invalid value for {opt}. val == {unparsed}"),
+                    match parse_time(&unparsed) {
+                        Ok(val) => config.short_break = val,
+                        Err(e) => {
+                            eprintln!("err: invalid value for {opt}. val == {unparsed}, err == {e}")
+                        }
                     }
                 }
                 "-l" | "--longbreak" => {
                     let unparsed = get_config_value_except(&options, opt);
-                    match unparsed.parse::<u16>() {
-                        Ok(val) => long_break = val * MINUTE,
-                        Err(_) => println!("err: invalid value for {opt}. val == {unparsed}"),
+                    match parse_time(&unparsed) {
+                        Ok(val) => config.long_break = val,
+                        Err(e) => {
+                            eprintln!("err: invalid value for {opt}. val == {unparsed}, err == {e}")
+                        }
                     }
                 }
                 "-i" | "--intervals" => {
                     let unparsed = get_config_value_except(&options, opt);
                     match unparsed.parse::<u8>() {
-                        Ok(val) => intervals = val,
-                        Err(_) => println!("err: invalid value for {opt}. val == {unparsed}"),
+                        Ok(0) => eprintln!("err: {opt} must be at least 1, ignoring"),
+                        Ok(val) => config.intervals = val,
+                        Err(_) => eprintln!("err: invalid value for {opt}. val == {unparsed}"),
+                    }
+                }
+                "--flowtime-ratio" => {
+                    let unparsed = get_config_value_except(&options, opt);
+                    match parse_ratio(&unparsed) {
+                        Ok(val) => config.flowtime_ratio = val,
+                        Err(e) => {
+                            eprintln!("err: invalid value for {opt}. val == {unparsed}, err == {e}")
+                        }
                     }
                 }
-                "-p" | "--play" => play_icon = get_config_value_except(&options, opt).clone(),
-                "-a" | "--pause" => pause_icon = get_config_value_except(&options, opt),
-                "-o" | "--work-icon" => work_icon = get_config_value_except(&options, opt),
-                "-b" | "--break-icon" => break_icon = get_config_value_except(&options, opt),
-                "--autow" => autow = true,
-                "--autob" => autob = true,
-                "--persist" => persist = true,
-                "--no-icons" => no_icons = true,
-                "--no-work-icons" => no_work_icons = true,
+                "--auto-start-delay" => {
+                    let unparsed = get_config_value_except(&options, opt);
+                    match parse_delay(&unparsed) {
+                        Ok(val) => config.auto_start_delay = val,
+                        Err(e) => {
+                            eprintln!("err: invalid value for {opt}. val == {unparsed}, err == {e}")
+                        }
+                    }
+                }
+                "--warning" => {
+                    let unparsed = get_config_value_except(&options, opt);
+                    match parse_delay(&unparsed) {
+                        Ok(val) => {
+                            config.work_warning = val;
+                            config.short_break_warning = val;
+                            config.long_break_warning = val;
+                        }
+                        Err(e) => {
+                            eprintln!("err: invalid value for {opt}. val == {unparsed}, err == {e}")
+                        }
+                    }
+                }
+                "--on-work-start" => {
+                    config.on_work_start = Some(get_config_value_except(&options, opt))
+                }
+                "--on-break-start" => {
+                    config.on_break_start = Some(get_config_value_except(&options, opt))
+                }
+                "--on-long-break-start" => {
+                    config.on_long_break_start = Some(get_config_value_except(&options, opt))
+                }
+                "--on-pause" => config.on_pause = Some(get_config_value_except(&options, opt)),
+                "--on-resume" => config.on_resume = Some(get_config_value_except(&options, opt)),
+                "--on-reset" => config.on_reset = Some(get_config_value_except(&options, opt)),
+                "--on-complete" => {
+                    config.on_complete = Some(get_config_value_except(&options, opt))
+                }
+                "--pause-media" => {
+                    let unparsed = get_config_value_except(&options, opt);
+                    match unparsed.parse() {
+                        Ok(val) => config.pause_media = val,
+                        Err(e) => {
+                            eprintln!("err: invalid value for {opt}. val == {unparsed}, err == {e}")
+                        }
+                    }
+                }
+                "--idle-reset" => {
+                    let unparsed = get_config_value_except(&options, opt);
+                    match parse_delay(&unparsed) {
+                        Ok(val) => config.idle_reset = val,
+                        Err(e) => {
+                            eprintln!("err: invalid value for {opt}. val == {unparsed}, err == {e}")
+                        }
+                    }
+                }
+                "--output" => {
+                    let unparsed = get_config_value_except(&options, opt);
+                    match unparsed.parse() {
+                        Ok(val) => config.output = val,
+                        Err(e) => {
+                            eprintln!("err: invalid value for {opt}. val == {unparsed}, err == {e}")
+                        }
+                    }
+                }
+                "--media-players" => {
+                    config.media_players = parse_players(&get_config_value_except(&options, opt))
+                }
+                "-p" | "--play" => config.play_icon = get_config_value_except(&options, opt),
+                "-a" | "--pause" => config.pause_icon = get_config_value_except(&options, opt),
+                "-o" | "--work-icon" => config.work_icon = get_config_value_except(&options, opt),
+                "-b" | "--break-icon" => config.break_icon = get_config_value_except(&options, opt),
+                "--work-color" => config.work_color = get_config_value_except(&options, opt),
+                "--break-color" => config.break_color = get_config_value_except(&options, opt),
+                "--pause-color" => config.pause_color = get_config_value_except(&options, opt),
+                "-f" | "--format" => config.format = get_config_value_except(&options, opt),
+                "--autow" => config.auto_work = true,
+                "--autob" => {
+                    config.auto_short_break = true;
+                    config.auto_long_break = true;
+                }
+                "--auto-short" => config.auto_short_break = true,
+                "--auto-long" => config.auto_long_break = true,
+                "--persist" => config.persist = true,
+                "--history" => config.history = true,
+                "--flowtime" => config.flowtime = true,
+                "--overtime" => config.overtime = true,
+                "--overtime-break" => config.overtime_break = true,
+                "--live-notification" => config.live_notification = true,
+                "--pause-on-lock" => config.pause_on_lock = true,
+                "--start-server" => config.start_server = true,
+                "--resume-on-unlock" => config.resume_on_unlock = true,
+                "--no-icons" => config.no_icons = true,
+                "--no-work-icons" => config.no_work_icons = true,
                 _ => (),
             }
         }
 
-        Self {
-            work_time,
-            short_break,
-            long_break,
-            intervals,
-            no_icons,
-            no_work_icons,
-            play_icon,
-            pause_icon,
-            work_icon,
-            break_icon,
-            autow,
-            autob,
-            persist,
-            binary_name,
+        // profiles fall back to the options above for anything they don't set
+        for (name, profile) in profiles.into_iter().flatten() {
+            let profile = profile.resolve(&config);
+            config.profiles.insert(name, profile);
         }
+
+        config
+    }
+
+    pub fn apply_profile(&mut self, name: &str) -> Result<(), String> {
+        let profile = match self.profiles.get(name) {
+            Some(profile) => profile.clone(),
+            None => return Err(format!("unknown profile \"{name}\"")),
+        };
+
+        self.work_time = profile.work_time;
+        self.short_break = profile.short_break;
+        self.long_break = profile.long_break;
+        self.intervals = profile.intervals;
+        self.auto_work = profile.auto_work;
+        self.auto_short_break = profile.auto_short_break;
+        self.auto_long_break = profile.auto_long_break;
+        self.profile = Some(name.to_string());
+        Ok(())
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        if let Some((phase, setting)) = notification_key(key) {
+            return set_notification(&mut self.notifications[phase as usize], setting, value);
+        }
+
+        match key {
+            "work_time" => self.work_time = parse_time(value)?,
+            "short_break" => self.short_break = parse_time(value)?,
+            "long_break" => self.long_break = parse_time(value)?,
+            "intervals" => match value.parse::<u8>() {
+                Ok(0) | Err(_) => {
+                    return Err(format!(
+                        "intervals must be a number between 1 and {}",
+                        u8::MAX
+                    ))
+                }
+                Ok(val) => self.intervals = val,
+            },
+            "no_icons" => self.no_icons = parse_bool(value)?,
+            "no_work_icons" => self.no_work_icons = parse_bool(value)?,
+            "play_icon" => self.play_icon = value.to_string(),
+            "pause_icon" => self.pause_icon = value.to_string(),
+            "work_icon" => self.work_icon = value.to_string(),
+            "break_icon" => self.break_icon = value.to_string(),
+            "work_color" => self.work_color = value.to_string(),
+            "break_color" => self.break_color = value.to_string(),
+            "pause_color" => self.pause_color = value.to_string(),
+            "auto_work" => self.auto_work = parse_bool(value)?,
+            "auto_short_break" => self.auto_short_break = parse_bool(value)?,
+            "auto_long_break" => self.auto_long_break = parse_bool(value)?,
+            "auto_start_delay" => self.auto_start_delay = parse_delay(value)?,
+            "work_warning" => self.work_warning = parse_delay(value)?,
+            "short_break_warning" => self.short_break_warning = parse_delay(value)?,
+            "long_break_warning" => self.long_break_warning = parse_delay(value)?,
+            "persist" => self.persist = parse_bool(value)?,
+            "history" => self.history = parse_bool(value)?,
+            "flowtime" => self.flowtime = parse_bool(value)?,
+            "flowtime_ratio" => self.flowtime_ratio = parse_ratio(value)?,
+            "overtime" => self.overtime = parse_bool(value)?,
+            "overtime_break" => self.overtime_break = parse_bool(value)?,
+            "live_notification" => self.live_notification = parse_bool(value)?,
+            "on_work_start" => self.on_work_start = parse_command(value),
+            "on_break_start" => self.on_break_start = parse_command(value),
+            "on_long_break_start" => self.on_long_break_start = parse_command(value),
+            "on_pause" => self.on_pause = parse_command(value),
+            "on_resume" => self.on_resume = parse_command(value),
+            "on_reset" => self.on_reset = parse_command(value),
+            "on_complete" => self.on_complete = parse_command(value),
+            "hook_timeout" => self.hook_timeout = parse_time(value)?,
+            "pause_media" => self.pause_media = value.parse()?,
+            "media_players" => self.media_players = parse_players(value),
+            "pause_on_lock" => self.pause_on_lock = parse_bool(value)?,
+            "resume_on_unlock" => self.resume_on_unlock = parse_bool(value)?,
+            "idle_reset" => self.idle_reset = parse_delay(value)?,
+            // an empty sequence goes back to the usual work/short/long cycle
+            "sequence" if value.trim().is_empty() => self.sequence = vec![],
+            "sequence" => self.sequence = parse_sequence(value)?,
+            "format" => self.format = value.to_string(),
+            "profile" => self.apply_profile(value)?,
+            "output" | "start_server" | "profiles" | "binary_name" => {
+                return Err(format!("{key} can't be changed at runtime"))
+            }
+            _ => return Err(unknown_key(key)),
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, key: &str) -> Result<Value, String> {
+        if let Some((phase, setting)) = notification_key(key) {
+            return Ok(get_notification(self.notification(phase), setting));
+        }
+
+        Ok(match key {
+            "work_time" => Value::Text(format_duration(self.work_time)),
+            "short_break" => Value::Text(format_duration(self.short_break)),
+            "long_break" => Value::Text(format_duration(self.long_break)),
+            "intervals" => Value::Number(self.intervals as u64),
+            "no_icons" => Value::Bool(self.no_icons),
+            "no_work_icons" => Value::Bool(self.no_work_icons),
+            "play_icon" => Value::Text(self.play_icon.clone()),
+            "pause_icon" => Value::Text(self.pause_icon.clone()),
+            "work_icon" => Value::Text(self.work_icon.clone()),
+            "break_icon" => Value::Text(self.break_icon.clone()),
+            "work_color" => Value::Text(self.work_color.clone()),
+            "break_color" => Value::Text(self.break_color.clone()),
+            "pause_color" => Value::Text(self.pause_color.clone()),
+            "auto_work" => Value::Bool(self.auto_work),
+            "auto_short_break" => Value::Bool(self.auto_short_break),
+            "auto_long_break" => Value::Bool(self.auto_long_break),
+            "auto_start_delay" => Value::Text(format_duration(self.auto_start_delay)),
+            "work_warning" => Value::Text(format_duration(self.work_warning)),
+            "short_break_warning" => Value::Text(format_duration(self.short_break_warning)),
+            "long_break_warning" => Value::Text(format_duration(self.long_break_warning)),
+            "persist" => Value::Bool(self.persist),
+            "history" => Value::Bool(self.history),
+            "flowtime" => Value::Bool(self.flowtime),
+            "flowtime_ratio" => Value::Text(self.flowtime_ratio.to_string()),
+            "overtime" => Value::Bool(self.overtime),
+            "overtime_break" => Value::Bool(self.overtime_break),
+            "live_notification" => Value::Bool(self.live_notification),
+            "on_work_start" => Value::Text(self.on_work_start.clone().unwrap_or_default()),
+            "on_break_start" => Value::Text(self.on_break_start.clone().unwrap_or_default()),
+            "on_long_break_start" => {
+                Value::Text(self.on_long_break_start.clone().unwrap_or_default())
+            }
+            "on_pause" => Value::Text(self.on_pause.clone().unwrap_or_default()),
+            "on_resume" => Value::Text(self.on_resume.clone().unwrap_or_default()),
+            "on_reset" => Value::Text(self.on_reset.clone().unwrap_or_default()),
+            "on_complete" => Value::Text(self.on_complete.clone().unwrap_or_default()),
+            "hook_timeout" => Value::Text(format_duration(self.hook_timeout)),
+            "pause_media" => Value::Text(self.pause_media.as_str().to_string()),
+            "media_players" => Value::List(self.media_players.clone()),
+            "pause_on_lock" => Value::Bool(self.pause_on_lock),
+            "resume_on_unlock" => Value::Bool(self.resume_on_unlock),
+            "idle_reset" => Value::Text(format_duration(self.idle_reset)),
+            "sequence" => Value::Text(format_sequence(&self.sequence)),
+            "format" => Value::Text(self.format.clone()),
+            "profile" => Value::Text(self.profile.clone().unwrap_or_default()),
+            "profiles" => Value::Text(
+                self.profiles
+                    .keys()
+                    .cloned()
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            ),
+            "output" => Value::Text(self.output.as_str().to_string()),
+            "start_server" => Value::Bool(self.start_server),
+            "binary_name" => Value::Text(self.binary_name.clone()),
+            _ => return Err(unknown_key(key)),
+        })
+    }
+
+    pub fn auto_start(&self, phase: Phase) -> bool {
+        match phase {
+            Phase::Work => self.auto_work,
+            Phase::ShortBreak => self.auto_short_break,
+            Phase::LongBreak => self.auto_long_break,
+        }
+    }
+
+    pub fn warning(&self, phase: Phase) -> u64 {
+        match phase {
+            Phase::Work => self.work_warning,
+            Phase::ShortBreak => self.short_break_warning,
+            Phase::LongBreak => self.long_break_warning,
+        }
+    }
+
+    pub fn notification(&self, phase: Phase) -> &NotificationConfig {
+        &self.notifications[phase as usize]
     }
 
     pub fn get_play_pause_icon(&self, running: bool) -> &str {
@@ -136,12 +640,15 @@ impl Config {
         }
     }
 
-    pub fn get_cycle_icon(&self, is_break: bool) -> &str {
+    // steps of a custom sequence can bring their own icon
+    pub fn get_cycle_icon<'a>(&'a self, is_break: bool, step_icon: Option<&'a str>) -> &'a str {
         if self.no_work_icons {
             return "";
         }
 
-        if !is_break {
+        if let Some(icon) = step_icon {
+            icon
+        } else if !is_break {
             &self.work_icon
         } else {
             &self.break_icon
@@ -149,6 +656,107 @@ impl Config {
     }
 }
 
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "true" | "on" | "yes" | "1" => Ok(true),
+        "false" | "off" | "no" | "0" => Ok(false),
+        _ => Err(format!("invalid value \"{value}\", expected true or false")),
+    }
+}
+
+// an empty command turns the hook off
+fn parse_command(value: &str) -> Option<String> {
+    Some(value.to_string()).filter(|command| !command.trim().is_empty())
+}
+
+// empty is the same as not set
+fn parse_optional(value: &str) -> Option<String> {
+    Some(value.to_string()).filter(|value| !value.is_empty())
+}
+
+// the phase and setting of e.g. notifications.short_break.urgency
+fn notification_key(key: &str) -> Option<(Phase, &str)> {
+    let (phase, setting) = key.strip_prefix("notifications.")?.split_once('.')?;
+    let phase = [Phase::Work, Phase::ShortBreak, Phase::LongBreak]
+        .into_iter()
+        .find(|p| p.as_str() == phase)?;
+    NOTIFICATION_KEYS
+        .contains(&setting)
+        .then_some((phase, setting))
+}
+
+fn set_notification(
+    notification: &mut NotificationConfig,
+    setting: &str,
+    value: &str,
+) -> Result<(), String> {
+    match setting {
+        "enabled" => notification.enabled = parse_bool(value)?,
+        "summary" => notification.summary = value.to_string(),
+        "body" => notification.body = parse_optional(value),
+        "icon" => notification.icon = parse_optional(value),
+        "urgency" if value.is_empty() => notification.urgency = None,
+        "urgency" => notification.urgency = Some(parse_urgency(value)?),
+        "timeout" if value.is_empty() => notification.timeout = None,
+        "timeout" => notification.timeout = Some(parse_delay(value)?),
+        "category" => notification.category = parse_optional(value),
+        "sound" => notification.sound = parse_optional(value),
+        _ => unreachable!("checked by notification_key"),
+    }
+    Ok(())
+}
+
+fn get_notification(notification: &NotificationConfig, setting: &str) -> Value {
+    match setting {
+        "enabled" => Value::Bool(notification.enabled),
+        "summary" => Value::Text(notification.summary.clone()),
+        "body" => Value::Text(notification.body.clone().unwrap_or_default()),
+        "icon" => Value::Text(notification.icon.clone().unwrap_or_default()),
+        "urgency" => Value::Text(
+            notification
+                .urgency
+                .map(format_urgency)
+                .unwrap_or_default()
+                .to_string(),
+        ),
+        "timeout" => Value::Text(
+            notification
+                .timeout
+                .map(format_duration)
+                .unwrap_or_default(),
+        ),
+        "category" => Value::Text(notification.category.clone().unwrap_or_default()),
+        "sound" => Value::Text(notification.sound.clone().unwrap_or_default()),
+        _ => unreachable!("checked by notification_key"),
+    }
+}
+
+fn unknown_key(key: &str) -> String {
+    format!(
+        "unknown option \"{key}\", expected one of: {} or notifications.<work|short_break|long_break>.<{}>",
+        KEYS.join(", "),
+        NOTIFICATION_KEYS.join("|")
+    )
+}
+
+fn load_config_file(options: &[String]) -> Option<ConfigFile> {
+    let filepath = match get_config_value(options, vec!["-c", "--config"]) {
+        Some(filepath) => PathBuf::from(filepath),
+        None => config_file::default_path().filter(|path| path.exists())?,
+    };
+
+    match ConfigFile::load(&filepath) {
+        Ok(file) => Some(file),
+        Err(e) => {
+            eprintln!(
+                "err: unable to load config file, path == {:?}, err == {e}",
+                filepath
+            );
+            None
+        }
+    }
+}
+
 fn get_config_value_except(options: &[String], opt: &str) -> String {
     get_config_value(options, vec![opt])
         .unwrap_or_else(|| panic!("err: {opt} specified but no value was provided"))
@@ -162,9 +770,27 @@ pub fn get_config_value<'a>(options: &'a [String], keys: Vec<&'a str>) -> Option
     }
 }
 
+// the first argument that's neither an option nor its value, e.g. status for
+// `waybar-module-pomodoro --format bar status`
+pub fn get_operation(options: &[String]) -> Option<&str> {
+    let mut args = options.iter().skip(1);
+    while let Some(arg) = args.next() {
+        if VALUE_OPTIONS.contains(&arg.as_str()) {
+            args.next();
+        } else if !arg.starts_with('-') {
+            return Some(arg);
+        }
+    }
+    None
+}
+
 pub fn parse_set_operations(args: Vec<String>) -> Vec<Message> {
     let mut set_operation: Vec<Message> = vec![];
-    for elem in SET_OPERATIONS {
+    for elem in SET_OPERATIONS
+        .into_iter()
+        .chain(ADJUST_OPERATIONS)
+        .chain([PROFILE_OPERATION])
+    {
         if !args.contains(&elem.to_string()) {
             continue;
         }
@@ -175,20 +801,54 @@ pub fn parse_set_operations(args: Vec<String>) -> Vec<Message> {
         }
 
         let val = val.unwrap();
-        if let Ok(val) = val.parse::<i32>() {
-            if val > 0 {
-                set_operation.push(Message::new(elem, val));
-            } else {
-                println!("{elem}: value must be higher than 0, ignoring");
+        // profile names are checked by the server, everything else is a duration
+        if elem != PROFILE_OPERATION {
+            if let Err(e) = parse_time(val) {
+                println!("{elem}: {e}, ignoring");
+                continue;
             }
         }
+        set_operation.push(Message::new(elem, val));
     }
     set_operation
 }
 
+// set <key> <value> [--save] and get <key>
+pub fn parse_config_operations(args: &[String]) -> Vec<Message> {
+    let Some(index) = args
+        .iter()
+        .position(|arg| CONFIG_OPERATIONS.contains(&arg.as_str()))
+    else {
+        return vec![];
+    };
+
+    let Some(key) = args.get(index + 1) else {
+        println!("{}: no option given, ignoring", args[index]);
+        return vec![];
+    };
+
+    if args[index] == "get" {
+        return vec![Message::new("get", key)];
+    }
+
+    let Some(value) = args.get(index + 2) else {
+        println!("set: no value given for {key}, ignoring");
+        return vec![];
+    };
+
+    let mut messages = vec![Message::new("set", &format!("{key} {value}"))];
+    if args.contains(&"--save".to_string()) {
+        messages.push(Message::new(SAVE_OPERATION, key));
+    }
+    messages
+}
+
 #[cfg(test)]
 mod tests {
+    use notify_rust::Urgency;
+
     use super::*;
+    use crate::MINUTE;
 
     #[test]
     fn test_config_from_options_default() {
@@ -204,9 +864,12 @@ mod tests {
         assert_eq!(config.pause_icon, PAUSE_ICON.to_string());
         assert_eq!(config.work_icon, WORK_ICON.to_string());
         assert_eq!(config.break_icon, BREAK_ICON.to_string());
-        assert!(!config.autow);
-        assert!(!config.autob);
+        assert!(!config.auto_work);
+        assert!(!config.auto_short_break);
+        assert!(!config.auto_long_break);
+        assert_eq!(config.auto_start_delay, 0);
         assert!(!config.persist);
+        assert!(!config.history);
         assert_eq!(config.binary_name, "waybar-module-pomodoro_test");
     }
 
@@ -244,8 +907,9 @@ mod tests {
         assert_eq!(config.pause_icon, "⏸️".to_string());
         assert_eq!(config.work_icon, "💻".to_string());
         assert_eq!(config.break_icon, "☕️".to_string());
-        assert!(config.autow);
-        assert!(!config.autob);
+        assert!(config.auto_work);
+        assert!(!config.auto_short_break);
+        assert!(!config.auto_long_break);
         assert!(config.persist);
     }
 
@@ -282,6 +946,193 @@ mod tests {
         assert_eq!(config.long_break, LONG_BREAK_TIME);
     }
 
+    #[test]
+    fn test_config_from_options_out_of_range() {
+        let options = vec![
+            "waybar-module-pomodoro_test".to_string(),
+            "-w".to_string(),
+            "100000".to_string(),
+            "-i".to_string(),
+            "0".to_string(),
+        ];
+        let config = Config::from_options(options);
+
+        assert_eq!(config.work_time, WORK_TIME);
+        assert_eq!(config.intervals, MAX_ITERATIONS);
+    }
+
+    #[test]
+    fn test_config_from_options_durations() {
+        let options = vec![
+            "waybar-module-pomodoro_test".to_string(),
+            "-w".to_string(),
+            "1h30m".to_string(),
+            "--shortbreak".to_string(),
+            "90s".to_string(),
+            "--longbreak".to_string(),
+            "0.5m".to_string(),
+        ];
+        let config = Config::from_options(options);
+
+        assert_eq!(config.work_time, 90 * MINUTE);
+        assert_eq!(config.short_break, 90);
+        assert_eq!(config.long_break, 30);
+    }
+
+    #[test]
+    fn test_config_from_options_flowtime() {
+        let options = vec![
+            "waybar-module-pomodoro_test".to_string(),
+            "--flowtime".to_string(),
+            "--flowtime-ratio".to_string(),
+            "1/4".to_string(),
+        ];
+        let config = Config::from_options(options);
+
+        assert!(config.flowtime);
+        assert_eq!(config.flowtime_ratio, 0.25);
+        assert_eq!(config.get("flowtime_ratio").unwrap().to_string(), "0.25");
+    }
+
+    #[test]
+    fn test_config_from_options_auto_start() {
+        let options = vec![
+            "waybar-module-pomodoro_test".to_string(),
+            "--auto-short".to_string(),
+            "--auto-start-delay".to_string(),
+            "10s".to_string(),
+        ];
+        let config = Config::from_options(options);
+
+        assert!(!config.auto_start(Phase::Work));
+        assert!(config.auto_start(Phase::ShortBreak));
+        assert!(!config.auto_start(Phase::LongBreak));
+        assert_eq!(config.auto_start_delay, 10);
+    }
+
+    #[test]
+    fn test_config_from_options_warning() {
+        let options = vec![
+            "waybar-module-pomodoro_test".to_string(),
+            "--warning".to_string(),
+            "1m".to_string(),
+        ];
+        let mut config = Config::from_options(options);
+
+        assert_eq!(config.warning(Phase::Work), 60);
+        assert_eq!(config.warning(Phase::LongBreak), 60);
+
+        config.set("short_break_warning", "0").unwrap();
+        assert_eq!(config.warning(Phase::ShortBreak), 0);
+        assert_eq!(config.warning(Phase::Work), 60);
+    }
+
+    #[test]
+    fn test_config_from_options_hooks() {
+        let options = vec![
+            "waybar-module-pomodoro_test".to_string(),
+            "--on-work-start".to_string(),
+            "notify-send focus".to_string(),
+            "--on-complete".to_string(),
+            "paplay bell.oga".to_string(),
+        ];
+        let mut config = Config::from_options(options);
+
+        assert_eq!(config.on_work_start, Some("notify-send focus".to_string()));
+        assert_eq!(config.on_complete, Some("paplay bell.oga".to_string()));
+        assert_eq!(config.on_pause, None);
+        assert_eq!(config.hook_timeout, HOOK_TIMEOUT);
+
+        config.set("on_complete", "").unwrap();
+        assert_eq!(config.on_complete, None);
+        config.set("hook_timeout", "30s").unwrap();
+        assert_eq!(config.get("hook_timeout").unwrap().to_string(), "30s");
+    }
+
+    #[test]
+    fn test_config_from_options_pause_media() {
+        let options = vec![
+            "waybar-module-pomodoro_test".to_string(),
+            "--pause-media".to_string(),
+            "break".to_string(),
+            "--media-players".to_string(),
+            "spotify,mpv".to_string(),
+        ];
+        let mut config = Config::from_options(options);
+
+        assert_eq!(config.pause_media, PauseMedia::Break);
+        assert_eq!(config.media_players, vec!["spotify", "mpv"]);
+        assert_eq!(
+            config.get("media_players").unwrap().to_string(),
+            "spotify, mpv"
+        );
+
+        assert!(config.set("pause_media", "always").is_err());
+        config.set("pause_media", "work").unwrap();
+        assert_eq!(config.pause_media, PauseMedia::Work);
+    }
+
+    #[test]
+    fn test_config_from_options_pause_on_lock() {
+        let options = vec![
+            "waybar-module-pomodoro_test".to_string(),
+            "--pause-on-lock".to_string(),
+            "--idle-reset".to_string(),
+            "10m".to_string(),
+        ];
+        let config = Config::from_options(options);
+
+        assert!(config.pause_on_lock);
+        assert!(!config.resume_on_unlock);
+        assert_eq!(config.idle_reset, 600);
+        assert_eq!(config.get("idle_reset").unwrap().to_string(), "10m");
+    }
+
+    #[test]
+    fn test_config_from_options_start_server() {
+        let options = vec![
+            "waybar-module-pomodoro_test".to_string(),
+            "toggle".to_string(),
+        ];
+        assert!(!Config::from_options(options.clone()).start_server);
+
+        let options = [options, vec!["--start-server".to_string()]].concat();
+        let mut config = Config::from_options(options);
+        assert!(config.start_server);
+        assert_eq!(
+            config.set("start_server", "false"),
+            Err("start_server can't be changed at runtime".to_string())
+        );
+    }
+
+    #[test]
+    fn test_config_from_options_config_file() {
+        let filepath = std::env::temp_dir().join("waybar-module-pomodoro_test_config.toml");
+        std::fs::write(
+            &filepath,
+            "work_time = \"50m\"\nshort_break = 10\nautob = true\n",
+        )
+        .unwrap();
+
+        let options = vec![
+            "waybar-module-pomodoro_test".to_string(),
+            "--config".to_string(),
+            filepath.to_string_lossy().to_string(),
+            "--shortbreak".to_string(),
+            "3m".to_string(),
+        ];
+        let config = Config::from_options(options);
+
+        assert_eq!(config.work_time, 50 * MINUTE);
+        // options on the command line take precedence over the file
+        assert_eq!(config.short_break, 3 * MINUTE);
+        assert_eq!(config.long_break, LONG_BREAK_TIME);
+        assert!(config.auto_short_break);
+        assert!(config.auto_long_break);
+
+        std::fs::remove_file(filepath).unwrap();
+    }
+
     #[test]
     fn test_config_from_options_no_icons() {
         let options = vec!["--no-icons".to_string()];
@@ -290,6 +1141,209 @@ mod tests {
         assert!(config.no_icons);
     }
 
+    #[test]
+    fn test_config_from_options_profiles() {
+        let filepath = std::env::temp_dir().join("waybar-module-pomodoro_test_profiles.toml");
+        std::fs::write(
+            &filepath,
+            "[profiles.meetings]\nwork_time = \"15m\"\nshort_break = \"3m\"\n",
+        )
+        .unwrap();
+
+        let options = vec![
+            "waybar-module-pomodoro_test".to_string(),
+            "--config".to_string(),
+            filepath.to_string_lossy().to_string(),
+            "--longbreak".to_string(),
+            "10m".to_string(),
+            "--autob".to_string(),
+        ];
+        let mut config = Config::from_options(options);
+        assert_eq!(config.profile, None);
+        assert_eq!(config.work_time, WORK_TIME);
+
+        config.apply_profile("meetings").unwrap();
+        assert_eq!(config.profile, Some("meetings".to_string()));
+        assert_eq!(config.work_time, 15 * MINUTE);
+        assert_eq!(config.short_break, 3 * MINUTE);
+        assert_eq!(config.long_break, 10 * MINUTE);
+        assert!(config.auto_short_break);
+        assert!(config.auto_long_break);
+
+        assert_eq!(
+            config.apply_profile("nope"),
+            Err("unknown profile \"nope\"".to_string())
+        );
+        assert_eq!(config.profile, Some("meetings".to_string()));
+
+        std::fs::remove_file(filepath).unwrap();
+    }
+
+    #[test]
+    fn test_set_and_get() {
+        let mut config = Config::default();
+
+        config.set("work_time", "1h30m").unwrap();
+        assert_eq!(config.work_time, 90 * MINUTE);
+        assert_eq!(
+            config.get("work_time"),
+            Ok(Value::Text("1h30m".to_string()))
+        );
+
+        config.set("intervals", "2").unwrap();
+        assert_eq!(config.get("intervals"), Ok(Value::Number(2)));
+
+        config.set("auto_short_break", "on").unwrap();
+        assert!(config.auto_short_break);
+        assert!(!config.auto_long_break);
+        assert_eq!(config.get("auto_short_break"), Ok(Value::Bool(true)));
+
+        config.set("auto_start_delay", "10s").unwrap();
+        assert_eq!(config.auto_start_delay, 10);
+        assert_eq!(
+            config.get("auto_start_delay"),
+            Ok(Value::Text("10s".to_string()))
+        );
+
+        config.set("work_icon", "W").unwrap();
+        assert_eq!(config.get("work_icon").unwrap().to_string(), "W");
+
+        config.set("sequence", "work 50m, long 10m").unwrap();
+        assert_eq!(config.sequence.len(), 2);
+        assert_eq!(
+            config.get("sequence").unwrap().to_string(),
+            "work 50m, long 10m"
+        );
+
+        config.set("format", "{remaining}").unwrap();
+        assert_eq!(config.format, "{remaining}");
+    }
+
+    #[test]
+    fn test_set_every_key() {
+        let mut config = Config {
+            work_time: WORK_TIME,
+            short_break: SHORT_BREAK_TIME,
+            long_break: LONG_BREAK_TIME,
+            ..Default::default()
+        };
+        for key in KEYS {
+            let value = config.get(key).unwrap().to_string();
+            match key {
+                "profile" | "output" | "start_server" | "profiles" | "binary_name" => {
+                    assert!(config.set(key, &value).is_err())
+                }
+                _ => assert_eq!(config.set(key, &value), Ok(()), "{key}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_set_notification() {
+        let mut config = Config::default();
+
+        assert_eq!(
+            config.set("notifications.short_break.enabled", "off"),
+            Ok(())
+        );
+        assert_eq!(config.set("notifications.work.urgency", "critical"), Ok(()));
+        assert_eq!(config.set("notifications.work.timeout", "0s"), Ok(()));
+        assert!(!config.notification(Phase::ShortBreak).enabled);
+        assert_eq!(
+            config.notification(Phase::Work).urgency,
+            Some(Urgency::Critical)
+        );
+        assert_eq!(config.notification(Phase::Work).timeout, Some(0));
+        assert_eq!(
+            config.get("notifications.work.urgency"),
+            Ok(Value::Text("critical".to_string()))
+        );
+
+        // empty goes back to the default
+        assert_eq!(config.set("notifications.work.urgency", ""), Ok(()));
+        assert_eq!(config.notification(Phase::Work).urgency, None);
+
+        for phase in ["work", "short_break", "long_break"] {
+            for setting in NOTIFICATION_KEYS {
+                let key = format!("notifications.{phase}.{setting}");
+                let value = config.get(&key).unwrap().to_string();
+                assert_eq!(config.set(&key, &value), Ok(()), "{key}");
+            }
+        }
+
+        assert!(config.set("notifications.work.urgency", "loud").is_err());
+        assert!(config.set("notifications.nap.enabled", "off").is_err());
+        assert!(config.get("notifications.work.color").is_err());
+    }
+
+    #[test]
+    fn test_set_invalid() {
+        let mut config = Config::default();
+
+        assert_eq!(
+            config.set("auto_work", "maybe"),
+            Err("invalid value \"maybe\", expected true or false".to_string())
+        );
+        assert!(config.set("intervals", "0").is_err());
+        assert!(config.set("intervals", "300").is_err());
+        assert!(config.set("work_time", "25x").is_err());
+        assert!(config.set("profile", "nope").is_err());
+        assert_eq!(
+            config.set("binary_name", "x"),
+            Err("binary_name can't be changed at runtime".to_string())
+        );
+        assert!(config
+            .set("nope", "1")
+            .unwrap_err()
+            .starts_with("unknown option \"nope\", expected one of: work_time"));
+        assert!(config.get("nope").is_err());
+        assert_eq!(config.intervals, MAX_ITERATIONS);
+        assert!(!config.auto_work);
+    }
+
+    #[test]
+    fn test_get_operation() {
+        let args = |list: &[&str]| list.iter().map(|x| x.to_string()).collect::<Vec<String>>();
+
+        assert_eq!(get_operation(&args(&["bin", "status"])), Some("status"));
+        assert_eq!(
+            get_operation(&args(&["bin", "--format", "status", "--no-icons", "watch"])),
+            Some("watch")
+        );
+        assert_eq!(
+            get_operation(&args(&["bin", "-o", "bar", "--output", "i3bar"])),
+            None
+        );
+        assert_eq!(
+            get_operation(&args(&["bin", "set", "format", "status"])),
+            Some("set")
+        );
+        assert_eq!(get_operation(&args(&["bin"])), None);
+    }
+
+    #[test]
+    fn test_parse_config_operations() {
+        let args = |list: &[&str]| list.iter().map(|x| x.to_string()).collect::<Vec<String>>();
+
+        assert_eq!(
+            parse_config_operations(&args(&["bin", "get", "intervals"])),
+            [Message::new("get", "intervals")]
+        );
+        assert_eq!(
+            parse_config_operations(&args(&["bin", "set", "format", "{remaining} {icon}"])),
+            [Message::new("set", "format {remaining} {icon}")]
+        );
+        assert_eq!(
+            parse_config_operations(&args(&["bin", "set", "auto_work", "true", "--save"])),
+            [
+                Message::new("set", "auto_work true"),
+                Message::new("save", "auto_work")
+            ]
+        );
+        assert!(parse_config_operations(&args(&["bin", "set", "auto_work"])).is_empty());
+        assert!(parse_config_operations(&args(&["bin", "toggle"])).is_empty());
+    }
+
     #[test]
     fn test_get_play_pause_icon_running() {
         let config = Config::default();
@@ -317,6 +1371,21 @@ mod tests {
         assert_eq!(icon, "");
     }
 
+    #[test]
+    fn test_get_cycle_icon() {
+        let config = Config::default();
+
+        assert_eq!(config.get_cycle_icon(false, None), WORK_ICON);
+        assert_eq!(config.get_cycle_icon(true, None), BREAK_ICON);
+        assert_eq!(config.get_cycle_icon(true, Some("☕")), "☕");
+
+        let config = Config {
+            no_work_icons: true,
+            ..Default::default()
+        };
+        assert_eq!(config.get_cycle_icon(true, Some("☕")), "");
+    }
+
     #[test]
     fn test_parse_set_operations_valid_values() {
         let args = vec![
@@ -328,8 +1397,45 @@ mod tests {
         let operations = parse_set_operations(args);
 
         assert_eq!(operations.len(), 2);
-        assert_eq!(operations[0], Message::new("set-work", 10));
-        assert_eq!(operations[1], Message::new("set-short", 5));
+        assert_eq!(operations[0], Message::new("set-work", "10"));
+        assert_eq!(operations[1], Message::new("set-short", "5"));
+    }
+
+    #[test]
+    fn test_parse_set_operations_durations() {
+        let args = vec![
+            "set-work".to_string(),
+            "1h30m".to_string(),
+            "set-long".to_string(),
+            "25x".to_string(),
+        ];
+        let operations = parse_set_operations(args);
+
+        assert_eq!(operations.len(), 1);
+        assert_eq!(operations[0], Message::new("set-work", "1h30m"));
+    }
+
+    #[test]
+    fn test_parse_set_operations_adjust() {
+        let args = vec![
+            "add".to_string(),
+            "+5".to_string(),
+            "sub".to_string(),
+            "2".to_string(),
+        ];
+        let operations = parse_set_operations(args);
+
+        assert_eq!(operations.len(), 2);
+        assert_eq!(operations[0], Message::new("add", "+5"));
+        assert_eq!(operations[1], Message::new("sub", "2"));
+    }
+
+    #[test]
+    fn test_parse_set_operations_profile() {
+        let args = vec!["profile".to_string(), "deep".to_string()];
+        let operations = parse_set_operations(args);
+
+        assert_eq!(operations, [Message::new("profile", "deep")]);
     }
 
     #[test]
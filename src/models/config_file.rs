@@ -0,0 +1,948 @@
+use std::{collections::BTreeMap, error::Error, fs, path::Path, path::PathBuf};
+
+use notify_rust::Urgency;
+use serde::{Deserialize, Deserializer};
+use toml_edit::{Array, DocumentMut, InlineTable, Item, Table, TableLike};
+
+use crate::utils::helper::{format_duration, parse_delay, parse_ratio, parse_time};
+
+use super::{
+    config::{Config, Value},
+    media::PauseMedia,
+    notification::{parse_urgency, NotificationConfig},
+    output::Output,
+    profile::Profile,
+    sequence::{default_name, parse_sequence, Step},
+};
+
+const MODULE: &str = env!("CARGO_PKG_NAME");
+const CONFIG_FILE: &str = "config.toml";
+
+// every field is optional, anything left out keeps its default value
+#[derive(Deserialize, Default, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    #[serde(default, deserialize_with = "deserialize_time")]
+    pub work_time: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_time")]
+    pub short_break: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_time")]
+    pub long_break: Option<u64>,
+    pub intervals: Option<u8>,
+    pub no_icons: Option<bool>,
+    pub no_work_icons: Option<bool>,
+    pub play_icon: Option<String>,
+    pub pause_icon: Option<String>,
+    pub work_icon: Option<String>,
+    pub break_icon: Option<String>,
+    pub work_color: Option<String>,
+    pub break_color: Option<String>,
+    pub pause_color: Option<String>,
+    // shorthands for auto_work, and for both auto_short_break and auto_long_break
+    pub autow: Option<bool>,
+    pub autob: Option<bool>,
+    pub auto_work: Option<bool>,
+    pub auto_short_break: Option<bool>,
+    pub auto_long_break: Option<bool>,
+    #[serde(default, deserialize_with = "deserialize_delay")]
+    pub auto_start_delay: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_delay")]
+    pub work_warning: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_delay")]
+    pub short_break_warning: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_delay")]
+    pub long_break_warning: Option<u64>,
+    pub persist: Option<bool>,
+    pub history: Option<bool>,
+    pub flowtime: Option<bool>,
+    #[serde(default, deserialize_with = "deserialize_ratio")]
+    pub flowtime_ratio: Option<f64>,
+    pub overtime: Option<bool>,
+    pub overtime_break: Option<bool>,
+    pub notifications: Option<RawNotifications>,
+    pub live_notification: Option<bool>,
+    pub on_work_start: Option<String>,
+    pub on_break_start: Option<String>,
+    pub on_long_break_start: Option<String>,
+    pub on_pause: Option<String>,
+    pub on_resume: Option<String>,
+    pub on_reset: Option<String>,
+    pub on_complete: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_time")]
+    pub hook_timeout: Option<u64>,
+    pub pause_media: Option<PauseMedia>,
+    pub media_players: Option<Vec<String>>,
+    pub pause_on_lock: Option<bool>,
+    pub resume_on_unlock: Option<bool>,
+    #[serde(default, deserialize_with = "deserialize_delay")]
+    pub idle_reset: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_sequence")]
+    pub sequence: Option<Vec<Step>>,
+    pub format: Option<String>,
+    pub profile: Option<String>,
+    pub profiles: Option<BTreeMap<String, RawProfile>>,
+    pub output: Option<Output>,
+    pub start_server: Option<bool>,
+}
+
+// notification settings for each phase, e.g. [notifications.work]
+#[derive(Deserialize, Default, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RawNotifications {
+    pub work: Option<RawNotification>,
+    pub short_break: Option<RawNotification>,
+    pub long_break: Option<RawNotification>,
+}
+
+#[derive(Deserialize, Default, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RawNotification {
+    pub enabled: Option<bool>,
+    pub summary: Option<String>,
+    pub body: Option<String>,
+    pub icon: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_urgency")]
+    pub urgency: Option<Urgency>,
+    #[serde(default, deserialize_with = "deserialize_delay")]
+    pub timeout: Option<u64>,
+    pub category: Option<String>,
+    pub sound: Option<String>,
+}
+
+impl RawNotification {
+    fn apply(self, notification: &mut NotificationConfig) {
+        if let Some(val) = self.enabled {
+            notification.enabled = val;
+        }
+        if let Some(val) = self.summary {
+            notification.summary = val;
+        }
+        if self.body.is_some() {
+            notification.body = self.body;
+        }
+        if self.icon.is_some() {
+            notification.icon = self.icon;
+        }
+        if self.urgency.is_some() {
+            notification.urgency = self.urgency;
+        }
+        if self.timeout.is_some() {
+            notification.timeout = self.timeout;
+        }
+        if self.category.is_some() {
+            notification.category = self.category;
+        }
+        if self.sound.is_some() {
+            notification.sound = self.sound;
+        }
+    }
+}
+
+fn deserialize_urgency<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Urgency>, D::Error> {
+    parse_urgency(&String::deserialize(deserializer)?)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+// anything a profile leaves out is taken from the rest of the config
+#[derive(Deserialize, Default, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RawProfile {
+    #[serde(default, deserialize_with = "deserialize_time")]
+    pub work_time: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_time")]
+    pub short_break: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_time")]
+    pub long_break: Option<u64>,
+    pub intervals: Option<u8>,
+    pub autow: Option<bool>,
+    pub autob: Option<bool>,
+    pub auto_work: Option<bool>,
+    pub auto_short_break: Option<bool>,
+    pub auto_long_break: Option<bool>,
+}
+
+impl RawProfile {
+    pub fn resolve(self, config: &Config) -> Profile {
+        Profile {
+            work_time: self.work_time.unwrap_or(config.work_time),
+            short_break: self.short_break.unwrap_or(config.short_break),
+            long_break: self.long_break.unwrap_or(config.long_break),
+            intervals: self.intervals.unwrap_or(config.intervals),
+            auto_work: self.auto_work.or(self.autow).unwrap_or(config.auto_work),
+            auto_short_break: self
+                .auto_short_break
+                .or(self.autob)
+                .unwrap_or(config.auto_short_break),
+            auto_long_break: self
+                .auto_long_break
+                .or(self.autob)
+                .unwrap_or(config.auto_long_break),
+        }
+    }
+}
+
+// durations can either be given as a string, e.g. "1h30m", or as a number of minutes
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawTime {
+    Minutes(u64),
+    Text(String),
+}
+
+impl RawTime {
+    fn parse(self) -> Result<u64, String> {
+        self.parse_with(parse_time)
+    }
+
+    fn parse_with(self, parse: fn(&str) -> Result<u64, String>) -> Result<u64, String> {
+        match self {
+            RawTime::Minutes(minutes) => parse(&minutes.to_string()),
+            RawTime::Text(text) => parse(&text),
+        }
+    }
+}
+
+fn deserialize_time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    RawTime::deserialize(deserializer)?
+        .parse()
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+fn deserialize_delay<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    RawTime::deserialize(deserializer)?
+        .parse_with(parse_delay)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+// ratios can either be given as a string, e.g. "1/5", or as a number
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawRatio {
+    Number(f64),
+    Text(String),
+}
+
+fn deserialize_ratio<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    match RawRatio::deserialize(deserializer)? {
+        RawRatio::Number(number) => parse_ratio(&number.to_string()),
+        RawRatio::Text(text) => parse_ratio(&text),
+    }
+    .map(Some)
+    .map_err(serde::de::Error::custom)
+}
+
+// a sequence is either written in its compact form, "work 50m, break 10m, long 30m",
+// or as a list of steps where each one can have its own name, icon and class
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawSequence {
+    Compact(String),
+    Steps(Vec<RawStep>),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawStep {
+    phase: String,
+    duration: RawTime,
+    name: Option<String>,
+    icon: Option<String>,
+    class: Option<String>,
+}
+
+impl RawStep {
+    fn parse(self) -> Result<Step, String> {
+        let phase = self.phase.parse()?;
+        Ok(Step {
+            name: self.name.unwrap_or_else(|| default_name(phase).to_string()),
+            phase,
+            duration: self.duration.parse()?,
+            icon: self.icon,
+            class: self.class,
+        })
+    }
+}
+
+fn deserialize_sequence<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<Step>>, D::Error> {
+    let steps = match RawSequence::deserialize(deserializer)? {
+        RawSequence::Compact(text) => parse_sequence(&text),
+        RawSequence::Steps(steps) => steps.into_iter().map(RawStep::parse).collect(),
+    }
+    .map_err(serde::de::Error::custom)?;
+
+    if steps.is_empty() {
+        return Err(serde::de::Error::custom(
+            "sequence must have at least one step",
+        ));
+    }
+    Ok(Some(steps))
+}
+
+impl ConfigFile {
+    pub fn load(filepath: &Path) -> Result<Self, Box<dyn Error>> {
+        Self::parse(&fs::read_to_string(filepath)?)
+    }
+
+    fn parse(content: &str) -> Result<Self, Box<dyn Error>> {
+        let file: Self = toml::from_str(content)?;
+        if file.intervals == Some(0)
+            || file
+                .profiles
+                .iter()
+                .flat_map(|profiles| profiles.values())
+                .any(|profile| profile.intervals == Some(0))
+        {
+            return Err("intervals must be at least 1".into());
+        }
+        Ok(file)
+    }
+
+    pub fn apply(self, config: &mut Config) {
+        if let Some(val) = self.work_time {
+            config.work_time = val;
+        }
+        if let Some(val) = self.short_break {
+            config.short_break = val;
+        }
+        if let Some(val) = self.long_break {
+            config.long_break = val;
+        }
+        if let Some(val) = self.intervals {
+            config.intervals = val;
+        }
+        if let Some(val) = self.no_icons {
+            config.no_icons = val;
+        }
+        if let Some(val) = self.no_work_icons {
+            config.no_work_icons = val;
+        }
+        if let Some(val) = self.play_icon {
+            config.play_icon = val;
+        }
+        if let Some(val) = self.pause_icon {
+            config.pause_icon = val;
+        }
+        if let Some(val) = self.work_icon {
+            config.work_icon = val;
+        }
+        if let Some(val) = self.break_icon {
+            config.break_icon = val;
+        }
+        if let Some(val) = self.work_color {
+            config.work_color = val;
+        }
+        if let Some(val) = self.break_color {
+            config.break_color = val;
+        }
+        if let Some(val) = self.pause_color {
+            config.pause_color = val;
+        }
+        if let Some(val) = self.auto_work.or(self.autow) {
+            config.auto_work = val;
+        }
+        if let Some(val) = self.auto_short_break.or(self.autob) {
+            config.auto_short_break = val;
+        }
+        if let Some(val) = self.auto_long_break.or(self.autob) {
+            config.auto_long_break = val;
+        }
+        if let Some(val) = self.auto_start_delay {
+            config.auto_start_delay = val;
+        }
+        if let Some(val) = self.work_warning {
+            config.work_warning = val;
+        }
+        if let Some(val) = self.short_break_warning {
+            config.short_break_warning = val;
+        }
+        if let Some(val) = self.long_break_warning {
+            config.long_break_warning = val;
+        }
+        if let Some(val) = self.persist {
+            config.persist = val;
+        }
+        if let Some(val) = self.history {
+            config.history = val;
+        }
+        if let Some(val) = self.flowtime {
+            config.flowtime = val;
+        }
+        if let Some(val) = self.flowtime_ratio {
+            config.flowtime_ratio = val;
+        }
+        if let Some(val) = self.overtime {
+            config.overtime = val;
+        }
+        if let Some(val) = self.overtime_break {
+            config.overtime_break = val;
+        }
+        if let Some(val) = self.live_notification {
+            config.live_notification = val;
+        }
+        if self.on_work_start.is_some() {
+            config.on_work_start = self.on_work_start;
+        }
+        if self.on_break_start.is_some() {
+            config.on_break_start = self.on_break_start;
+        }
+        if self.on_long_break_start.is_some() {
+            config.on_long_break_start = self.on_long_break_start;
+        }
+        if self.on_pause.is_some() {
+            config.on_pause = self.on_pause;
+        }
+        if self.on_resume.is_some() {
+            config.on_resume = self.on_resume;
+        }
+        if self.on_reset.is_some() {
+            config.on_reset = self.on_reset;
+        }
+        if self.on_complete.is_some() {
+            config.on_complete = self.on_complete;
+        }
+        if let Some(val) = self.hook_timeout {
+            config.hook_timeout = val;
+        }
+        if let Some(val) = self.pause_media {
+            config.pause_media = val;
+        }
+        if let Some(val) = self.media_players {
+            config.media_players = val;
+        }
+        if let Some(val) = self.pause_on_lock {
+            config.pause_on_lock = val;
+        }
+        if let Some(val) = self.resume_on_unlock {
+            config.resume_on_unlock = val;
+        }
+        if let Some(val) = self.idle_reset {
+            config.idle_reset = val;
+        }
+        if let Some(notifications) = self.notifications {
+            let phases = [
+                notifications.work,
+                notifications.short_break,
+                notifications.long_break,
+            ];
+            for (raw, notification) in phases.into_iter().zip(config.notifications.iter_mut()) {
+                if let Some(raw) = raw {
+                    raw.apply(notification);
+                }
+            }
+        }
+        if let Some(val) = self.sequence {
+            config.sequence = val;
+        }
+        if let Some(val) = self.format {
+            config.format = val;
+        }
+        if let Some(val) = self.output {
+            config.output = val;
+        }
+        if let Some(val) = self.start_server {
+            config.start_server = val;
+        }
+        if let Some(val) = self.profile {
+            config.profile = Some(val);
+        }
+    }
+}
+
+// steps are written out in full, the compact form would lose their names, icons and classes
+fn sequence_item(steps: &[Step]) -> Item {
+    let mut array = Array::new();
+    for step in steps {
+        let mut table = InlineTable::new();
+        table.insert("phase", step.phase.as_str().into());
+        table.insert("duration", format_duration(step.duration).into());
+        if step.name != default_name(step.phase) {
+            table.insert("name", step.name.as_str().into());
+        }
+        if let Some(icon) = &step.icon {
+            table.insert("icon", icon.as_str().into());
+        }
+        if let Some(class) = &step.class {
+            table.insert("class", class.as_str().into());
+        }
+        array.push(table);
+    }
+    toml_edit::value(array)
+}
+
+// the option as it's written to the config file, none if it's better left out
+fn item(config: &Config, key: &str) -> Result<Option<Item>, String> {
+    let value = config.get(key)?;
+    // empty is the same as not set for these, e.g. no sequence or no hook
+    if value.to_string().is_empty()
+        && Config::default()
+            .get(key)
+            .is_ok_and(|default| default.to_string().is_empty())
+    {
+        return Ok(None);
+    }
+
+    Ok(Some(match (key, value) {
+        ("sequence", _) => sequence_item(&config.sequence),
+        (_, Value::Bool(val)) => toml_edit::value(val),
+        (_, Value::Number(val)) => toml_edit::value(val as i64),
+        (_, Value::Text(val)) => toml_edit::value(val),
+        (_, Value::List(val)) => toml_edit::value(Array::from_iter(val)),
+    }))
+}
+
+// the table at `path`, creating the ones that are missing
+fn table_mut<'a>(
+    document: &'a mut DocumentMut,
+    path: &[&str],
+) -> Result<&'a mut dyn TableLike, String> {
+    let mut table: &mut dyn TableLike = document.as_table_mut();
+    for name in path {
+        table = table
+            .entry(name)
+            .or_insert_with(|| {
+                // only written out once something is in it
+                let mut table = Table::new();
+                table.set_implicit(true);
+                Item::Table(table)
+            })
+            .as_table_like_mut()
+            .ok_or_else(|| format!("{name} isn't a table"))?;
+    }
+    Ok(table)
+}
+
+// writes a single option to the config file, keeping the rest of the file as it is
+pub fn save(filepath: &Path, key: &str, config: &Config) -> Result<(), Box<dyn Error>> {
+    let content = match fs::read_to_string(filepath) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let mut document = content.parse::<DocumentMut>()?;
+
+    // keys of notifications are nested, e.g. notifications.work.enabled is in [notifications.work]
+    let path: Vec<&str> = key.split('.').collect();
+    let (name, tables) = path.split_last().unwrap();
+    let table = table_mut(&mut document, tables)?;
+    match item(config, key)? {
+        // replaced in place, so comments in front of it stay
+        Some(item) => *table.entry(name).or_insert(Item::None) = item,
+        None => {
+            table.remove(name);
+        }
+    }
+
+    // a file that doesn't load would be ignored as a whole on the next start
+    if let Err(e) = ConfigFile::parse(&document.to_string()) {
+        return Err(format!("the file wouldn't load anymore, {e}").into());
+    }
+
+    if let Some(dir) = filepath.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    Ok(fs::write(filepath, document.to_string())?)
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    let mut dir = dirs::config_dir()?;
+    dir.push(MODULE);
+    dir.push(CONFIG_FILE);
+    Some(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::phase::Phase;
+
+    #[test]
+    fn test_parse_config_file() {
+        let file: ConfigFile = toml::from_str(
+            r#"
+            work_time = "1h30m"
+            short_break = 10
+            long_break = "90s"
+            intervals = 2
+            autob = true
+            work_icon = "W"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(file.work_time, Some(90 * 60));
+        assert_eq!(file.short_break, Some(10 * 60));
+        assert_eq!(file.long_break, Some(90));
+        assert_eq!(file.intervals, Some(2));
+        assert_eq!(file.autob, Some(true));
+        assert_eq!(file.work_icon, Some("W".to_string()));
+        assert_eq!(file.autow, None);
+    }
+
+    #[test]
+    fn test_parse_config_file_invalid_duration() {
+        let result = toml::from_str::<ConfigFile>(r#"work_time = "25x""#);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_config_file_unknown_key() {
+        let result = toml::from_str::<ConfigFile>(r#"worktime = "25m""#);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_config_file_flowtime_ratio() {
+        let file: ConfigFile = toml::from_str("flowtime = true\nflowtime_ratio = \"1/5\"").unwrap();
+        assert_eq!(file.flowtime, Some(true));
+        assert_eq!(file.flowtime_ratio, Some(0.2));
+
+        let file: ConfigFile = toml::from_str("flowtime_ratio = 0.5").unwrap();
+        assert_eq!(file.flowtime_ratio, Some(0.5));
+
+        assert!(toml::from_str::<ConfigFile>("flowtime_ratio = 2").is_err());
+    }
+
+    #[test]
+    fn test_parse_config_file_compact_sequence() {
+        let file: ConfigFile = toml::from_str(r#"sequence = "work 52m, break 17m""#).unwrap();
+
+        assert_eq!(
+            file.sequence,
+            Some(vec![
+                Step::new(Phase::Work, 52 * 60),
+                Step::new(Phase::ShortBreak, 17 * 60)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_config_file_sequence_steps() {
+        let file: ConfigFile = toml::from_str(
+            r#"
+            [[sequence]]
+            name = "ultradian"
+            phase = "work"
+            duration = "90m"
+            icon = "U"
+            class = "deep"
+
+            [[sequence]]
+            phase = "long"
+            duration = 20
+            "#,
+        )
+        .unwrap();
+
+        let steps = file.sequence.unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].name, "ultradian");
+        assert_eq!(steps[0].phase, Phase::Work);
+        assert_eq!(steps[0].duration, 90 * 60);
+        assert_eq!(steps[0].icon, Some("U".to_string()));
+        assert_eq!(steps[0].class, Some("deep".to_string()));
+        assert_eq!(steps[1], Step::new(Phase::LongBreak, 20 * 60));
+    }
+
+    #[test]
+    fn test_parse_config_file_invalid_sequence() {
+        assert!(toml::from_str::<ConfigFile>(r#"sequence = "work""#).is_err());
+        assert!(toml::from_str::<ConfigFile>("sequence = []").is_err());
+        assert!(
+            toml::from_str::<ConfigFile>(r#"sequence = [{ phase = "nap", duration = "5m" }]"#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_config_file_profiles() {
+        let file: ConfigFile = toml::from_str(
+            r#"
+            autob = true
+            profile = "classic"
+
+            [profiles.deep]
+            work_time = "50m"
+            short_break = "10m"
+            long_break = "30m"
+
+            [profiles.classic]
+            work_time = 25
+            intervals = 2
+            autob = false
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(file.profile, Some("classic".to_string()));
+
+        let config = Config {
+            work_time: 1,
+            short_break: 2,
+            long_break: 3,
+            intervals: 4,
+            auto_short_break: true,
+            auto_long_break: true,
+            ..Default::default()
+        };
+        let mut profiles = file.profiles.unwrap();
+        assert_eq!(
+            profiles.remove("deep").unwrap().resolve(&config),
+            Profile {
+                work_time: 50 * 60,
+                short_break: 10 * 60,
+                long_break: 30 * 60,
+                intervals: 4,
+                auto_work: false,
+                auto_short_break: true,
+                auto_long_break: true,
+            }
+        );
+        assert_eq!(
+            profiles.remove("classic").unwrap().resolve(&config),
+            Profile {
+                work_time: 25 * 60,
+                short_break: 2,
+                long_break: 3,
+                intervals: 2,
+                auto_work: false,
+                auto_short_break: false,
+                auto_long_break: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_config_file_notifications() {
+        let file: ConfigFile = toml::from_str(
+            r#"
+            [notifications.work]
+            summary = "Focus"
+            body = "{phase} for {remaining}"
+            urgency = "critical"
+            timeout = "10s"
+            sound = "bell"
+
+            [notifications.short_break]
+            enabled = false
+            "#,
+        )
+        .unwrap();
+
+        let mut config = Config::default();
+        file.apply(&mut config);
+
+        let work = config.notification(Phase::Work);
+        assert_eq!(work.summary, "Focus");
+        assert_eq!(work.body, Some("{phase} for {remaining}".to_string()));
+        assert_eq!(work.urgency, Some(Urgency::Critical));
+        assert_eq!(work.timeout, Some(10));
+        assert_eq!(work.sound, Some("bell".to_string()));
+        assert!(work.enabled);
+        assert!(!config.notification(Phase::ShortBreak).enabled);
+        assert_eq!(
+            *config.notification(Phase::LongBreak),
+            NotificationConfig::default()
+        );
+
+        assert!(toml::from_str::<ConfigFile>("[notifications.work]\nurgency = \"loud\"").is_err());
+        assert!(toml::from_str::<ConfigFile>("[notifications.nap]\nenabled = false").is_err());
+    }
+
+    #[test]
+    fn test_parse_config_file_hooks() {
+        let file: ConfigFile =
+            toml::from_str("on_break_start = \"playerctl pause\"\nhook_timeout = \"1m\"").unwrap();
+
+        let mut config = Config::default();
+        file.apply(&mut config);
+
+        assert_eq!(config.on_break_start, Some("playerctl pause".to_string()));
+        assert_eq!(config.on_work_start, None);
+        assert_eq!(config.hook_timeout, 60);
+    }
+
+    #[test]
+    fn test_parse_config_file_pause_media() {
+        let file: ConfigFile =
+            toml::from_str("pause_media = \"break\"\nmedia_players = [\"spotify\"]").unwrap();
+        assert_eq!(file.pause_media, Some(PauseMedia::Break));
+        assert_eq!(file.media_players, Some(vec!["spotify".to_string()]));
+
+        assert!(toml::from_str::<ConfigFile>("pause_media = \"always\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_config_file_output() {
+        let file: ConfigFile =
+            toml::from_str("output = \"i3bar\"\nwork_color = \"#FF0000\"").unwrap();
+
+        let mut config = Config::default();
+        file.apply(&mut config);
+
+        assert_eq!(config.output, Output::I3bar);
+        assert_eq!(config.work_color, "#FF0000");
+        assert!(!config.start_server);
+
+        let file: ConfigFile = toml::from_str("start_server = true").unwrap();
+        file.apply(&mut config);
+        assert!(config.start_server);
+        assert!(toml::from_str::<ConfigFile>("output = \"dzen\"").is_err());
+    }
+
+    #[test]
+    fn test_save() -> Result<(), Box<dyn Error>> {
+        let filepath = std::env::temp_dir().join("waybar-module-pomodoro_test_save.toml");
+        fs::write(
+            &filepath,
+            "# my settings\nwork_time = \"25m\"\nautob = false\n",
+        )?;
+
+        let config = Config {
+            work_time: 50 * 60,
+            auto_short_break: true,
+            intervals: 2,
+            ..Default::default()
+        };
+        save(&filepath, "work_time", &config)?;
+        save(&filepath, "auto_short_break", &config)?;
+        save(&filepath, "intervals", &config)?;
+
+        assert_eq!(
+            fs::read_to_string(&filepath)?,
+            "# my settings\nwork_time = \"50m\"\nautob = false\nauto_short_break = true\nintervals = 2\n"
+        );
+        let file = ConfigFile::load(&filepath)?;
+        assert_eq!(file.work_time, Some(50 * 60));
+        assert_eq!(file.intervals, Some(2));
+
+        fs::remove_file(filepath)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_sequence() -> Result<(), Box<dyn Error>> {
+        let filepath = std::env::temp_dir().join("waybar-module-pomodoro_test_save_sequence.toml");
+        fs::write(&filepath, "work_time = \"25m\"\n")?;
+
+        let mut focus = Step::new(Phase::Work, 50 * 60);
+        focus.name = "deep \"focus\"".to_string();
+        focus.icon = Some("🧠".to_string());
+        focus.class = Some("deep".to_string());
+        let mut config = Config {
+            sequence: vec![focus, Step::new(Phase::ShortBreak, 10 * 60)],
+            ..Default::default()
+        };
+        save(&filepath, "sequence", &config)?;
+
+        // nothing is lost on the way through the file
+        let mut restored = Config::default();
+        ConfigFile::load(&filepath)?.apply(&mut restored);
+        assert_eq!(restored.sequence, config.sequence);
+
+        // an empty sequence goes back to the usual cycle, which is the same as leaving it out
+        config.sequence = vec![];
+        save(&filepath, "sequence", &config)?;
+        assert_eq!(fs::read_to_string(&filepath)?, "work_time = \"25m\"\n");
+
+        fs::remove_file(filepath)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_notification() -> Result<(), Box<dyn Error>> {
+        let filepath =
+            std::env::temp_dir().join("waybar-module-pomodoro_test_save_notification.toml");
+        fs::write(&filepath, "work_time = \"25m\"\n")?;
+
+        let mut config = Config::default();
+        config.set("notifications.short_break.enabled", "false")?;
+        config.set("notifications.short_break.urgency", "low")?;
+        save(&filepath, "notifications.short_break.enabled", &config)?;
+        save(&filepath, "notifications.short_break.urgency", &config)?;
+        assert_eq!(
+            fs::read_to_string(&filepath)?,
+            "work_time = \"25m\"\n\n[notifications.short_break]\nenabled = false\nurgency = \"low\"\n"
+        );
+
+        let mut restored = Config::default();
+        ConfigFile::load(&filepath)?.apply(&mut restored);
+        assert_eq!(
+            restored.notification(Phase::ShortBreak),
+            config.notification(Phase::ShortBreak)
+        );
+
+        fs::remove_file(filepath)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_media_players() -> Result<(), Box<dyn Error>> {
+        let filepath = std::env::temp_dir().join("waybar-module-pomodoro_test_save_media.toml");
+        let _ = fs::remove_file(&filepath);
+
+        let mut config = Config::default();
+        config.set("media_players", "spotify, mpv")?;
+        save(&filepath, "media_players", &config)?;
+        assert_eq!(
+            fs::read_to_string(&filepath)?,
+            "media_players = [\"spotify\", \"mpv\"]\n"
+        );
+
+        let mut restored = Config::default();
+        ConfigFile::load(&filepath)?.apply(&mut restored);
+        assert_eq!(restored.media_players, ["spotify", "mpv"]);
+
+        // back to all players
+        config.set("media_players", "")?;
+        save(&filepath, "media_players", &config)?;
+        assert_eq!(fs::read_to_string(&filepath)?, "");
+
+        fs::remove_file(filepath)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_invalid() -> Result<(), Box<dyn Error>> {
+        let filepath = std::env::temp_dir().join("waybar-module-pomodoro_test_save_invalid.toml");
+        fs::write(&filepath, "work_time = \"25m\"\n")?;
+
+        // a value the file doesn't accept is refused, leaving the file as it was
+        let config = Config {
+            intervals: 0,
+            ..Default::default()
+        };
+        assert!(save(&filepath, "intervals", &config).is_err());
+        assert_eq!(fs::read_to_string(&filepath)?, "work_time = \"25m\"\n");
+
+        fs::remove_file(filepath)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut config = Config::default();
+        let file = ConfigFile {
+            work_time: Some(50 * 60),
+            autow: Some(true),
+            auto_long_break: Some(true),
+            auto_start_delay: Some(10),
+            ..Default::default()
+        };
+
+        file.apply(&mut config);
+
+        assert_eq!(config.work_time, 50 * 60);
+        assert_eq!(config.short_break, 0);
+        assert!(config.auto_work);
+        assert!(!config.auto_short_break);
+        assert!(config.auto_long_break);
+        assert_eq!(config.auto_start_delay, 10);
+    }
+}
@@ -0,0 +1,84 @@
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use super::phase::Phase;
+
+// the phases media players are paused for, they're played again once the phase is over
+#[derive(Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PauseMedia {
+    #[default]
+    Off,
+    Break,
+    Work,
+}
+
+impl FromStr for PauseMedia {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "off" => Ok(PauseMedia::Off),
+            "break" => Ok(PauseMedia::Break),
+            "work" => Ok(PauseMedia::Work),
+            _ => Err(format!(
+                "unknown value \"{input}\", expected off, break or work"
+            )),
+        }
+    }
+}
+
+impl PauseMedia {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PauseMedia::Off => "off",
+            PauseMedia::Break => "break",
+            PauseMedia::Work => "work",
+        }
+    }
+
+    pub fn pauses(&self, phase: Phase) -> bool {
+        match self {
+            PauseMedia::Off => false,
+            PauseMedia::Break => phase.is_break(),
+            PauseMedia::Work => !phase.is_break(),
+        }
+    }
+}
+
+// a comma separated list of player names, e.g. "spotify, mpv"
+pub fn parse_players(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pause_media() {
+        for mode in [PauseMedia::Off, PauseMedia::Break, PauseMedia::Work] {
+            assert_eq!(mode.as_str().parse(), Ok(mode));
+        }
+        assert!("always".parse::<PauseMedia>().is_err());
+    }
+
+    #[test]
+    fn test_pauses() {
+        assert!(PauseMedia::Break.pauses(Phase::LongBreak));
+        assert!(!PauseMedia::Break.pauses(Phase::Work));
+        assert!(PauseMedia::Work.pauses(Phase::Work));
+        assert!(!PauseMedia::Off.pauses(Phase::ShortBreak));
+    }
+
+    #[test]
+    fn test_parse_players() {
+        assert_eq!(parse_players("spotify, mpv,"), vec!["spotify", "mpv"]);
+        assert!(parse_players(" ").is_empty());
+    }
+}
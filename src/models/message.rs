@@ -5,14 +5,14 @@ use regex::Regex;
 #[derive(Debug, PartialEq)]
 pub struct Message {
     name: String,
-    value: i32,
+    value: String,
 }
 
 impl Message {
-    pub fn new(name: &str, value: i32) -> Self {
+    pub fn new(name: &str, value: &str) -> Self {
         Self {
             name: String::from(name),
-            value,
+            value: String::from(value),
         }
     }
 
@@ -20,22 +20,25 @@ impl Message {
         &self.name
     }
 
-    pub fn value(&self) -> i32 {
-        self.value
+    pub fn value(&self) -> &str {
+        &self.value
     }
 
     pub fn decode(input: &str) -> Result<Self, Box<dyn Error>> {
-        let re = Regex::new(r"\[(.*?)\;(.*?)\]").unwrap();
+        // values can contain anything, e.g. a format string with brackets in it
+        let re = Regex::new(r"(?s)^\[(.*?);(.*)\]$").unwrap();
         match re.captures(input) {
             Some(caps) => {
                 let extracted: (&str, [&str; 2]) = caps.extract();
-                println!("{:?}", extracted);
                 if extracted.1[0].is_empty() {
                     return Err(format!("message name is missing. msg == {:?}", extracted).into());
                 }
+                if extracted.1[1].is_empty() {
+                    return Err(format!("message value is missing. msg == {:?}", extracted).into());
+                }
                 Ok(Self {
                     name: extracted.1[0].to_string(),
-                    value: extracted.1[1].parse()?,
+                    value: extracted.1[1].to_string(),
                 })
             }
             None => Err(format!("unable to decode message: {input}").into()),
@@ -53,26 +56,26 @@ mod tests {
 
     #[test]
     fn test_new() {
-        let message = Message::new("example", 42);
+        let message = Message::new("example", "42");
         assert_eq!(message.name, "example");
-        assert_eq!(message.value, 42);
+        assert_eq!(message.value, "42");
     }
 
     #[test]
     fn test_name() {
-        let message = Message::new("example", 42);
+        let message = Message::new("example", "42");
         assert_eq!(message.name(), "example");
     }
 
     #[test]
     fn test_value() {
-        let message = Message::new("example", 42);
-        assert_eq!(message.value(), 42);
+        let message = Message::new("example", "42");
+        assert_eq!(message.value(), "42");
     }
 
     #[test]
     fn test_encode() {
-        let message = Message::new("example", 42);
+        let message = Message::new("example", "42");
         assert_eq!(message.encode(), "[example;42]");
     }
 
@@ -83,7 +86,7 @@ mod tests {
         assert!(result.is_ok());
         let message = result.unwrap();
         assert_eq!(message.name, "example");
-        assert_eq!(message.value, 42);
+        assert_eq!(message.value, "42");
     }
 
     #[test]
@@ -115,18 +118,28 @@ mod tests {
         let result = Message::decode(input);
         assert!(result.is_err());
         if let Err(e) = result {
-            assert_eq!(e.to_string(), "cannot parse integer from empty string");
+            assert_eq!(
+                e.to_string(),
+                "message value is missing. msg == (\"[example;]\", [\"example\", \"\"])"
+            );
         }
     }
 
     #[test]
-    fn test_decode_failure_not_a_number() {
-        let input = "[example;not_a_number]";
+    fn test_decode_duration_value() {
+        let input = "[set-work;1h30m]";
         let result = Message::decode(input);
-        assert!(result.is_err());
-        if let Err(e) = result {
-            assert_eq!(e.to_string(), "invalid digit found in string");
-        }
+        assert!(result.is_ok());
+        let message = result.unwrap();
+        assert_eq!(message.name, "set-work");
+        assert_eq!(message.value, "1h30m");
+    }
+
+    #[test]
+    fn test_decode_value_with_brackets() {
+        let message = Message::new("set", "format [{profile}]; {remaining}");
+        let result = Message::decode(&message.encode());
+        assert_eq!(result.unwrap(), message);
     }
 
     #[test]
@@ -1,2 +1,9 @@
 pub mod config;
+pub mod config_file;
+pub mod media;
 pub mod message;
+pub mod notification;
+pub mod output;
+pub mod phase;
+pub mod profile;
+pub mod sequence;
@@ -0,0 +1,52 @@
+use notify_rust::Urgency;
+
+use crate::utils::consts::SUMMARY;
+
+// how the notification at the start of a phase looks, set per phase in the config file
+#[derive(Debug, PartialEq, Clone)]
+pub struct NotificationConfig {
+    pub enabled: bool,
+    pub summary: String,
+    // uses the same placeholders as the bar, the built-in message is used when not set
+    pub body: Option<String>,
+    pub icon: Option<String>,
+    pub urgency: Option<Urgency>,
+    // seconds until it expires, 0 keeps it around until dismissed
+    pub timeout: Option<u64>,
+    pub category: Option<String>,
+    pub sound: Option<String>,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            summary: SUMMARY.to_string(),
+            body: None,
+            icon: None,
+            urgency: None,
+            timeout: None,
+            category: None,
+            sound: None,
+        }
+    }
+}
+
+pub fn parse_urgency(input: &str) -> Result<Urgency, String> {
+    match input {
+        "low" => Ok(Urgency::Low),
+        "normal" => Ok(Urgency::Normal),
+        "critical" => Ok(Urgency::Critical),
+        _ => Err(format!(
+            "unknown urgency \"{input}\", expected low, normal or critical"
+        )),
+    }
+}
+
+pub fn format_urgency(urgency: Urgency) -> &'static str {
+    match urgency {
+        Urgency::Low => "low",
+        Urgency::Normal => "normal",
+        Urgency::Critical => "critical",
+    }
+}
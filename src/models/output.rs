@@ -0,0 +1,69 @@
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+// the bar the module is talking to, which decides how each update is printed
+#[derive(Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Output {
+    #[default]
+    Waybar,
+    I3bar,
+    Plain,
+    Polybar,
+    Lemonbar,
+    // prints nothing, for a daemon that leaves showing the timer to bars of its own
+    None,
+}
+
+impl FromStr for Output {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "waybar" => Ok(Output::Waybar),
+            "i3bar" | "swaybar" => Ok(Output::I3bar),
+            "plain" => Ok(Output::Plain),
+            "polybar" => Ok(Output::Polybar),
+            "lemonbar" => Ok(Output::Lemonbar),
+            "none" => Ok(Output::None),
+            _ => Err(format!(
+                "unknown output \"{input}\", expected waybar, i3bar, plain, polybar, lemonbar or none"
+            )),
+        }
+    }
+}
+
+impl Output {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Output::Waybar => "waybar",
+            Output::I3bar => "i3bar",
+            Output::Plain => "plain",
+            Output::Polybar => "polybar",
+            Output::Lemonbar => "lemonbar",
+            Output::None => "none",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_output() {
+        for output in [
+            Output::Waybar,
+            Output::I3bar,
+            Output::Plain,
+            Output::Polybar,
+            Output::Lemonbar,
+            Output::None,
+        ] {
+            assert_eq!(output.as_str().parse(), Ok(output));
+        }
+        assert_eq!("swaybar".parse(), Ok(Output::I3bar));
+        assert!("dzen".parse::<Output>().is_err());
+    }
+}
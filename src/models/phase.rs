@@ -0,0 +1,157 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl FromStr for Phase {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "work" => Ok(Phase::Work),
+            "break" | "short" | "short_break" => Ok(Phase::ShortBreak),
+            "long" | "long_break" => Ok(Phase::LongBreak),
+            _ => Err(format!(
+                "unknown phase \"{input}\", expected work, break or long"
+            )),
+        }
+    }
+}
+
+// caches written before phases had names stored the index of the phase instead
+pub fn deserialize_cached<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Phase, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Cached {
+        Phase(Phase),
+        Index(u64),
+    }
+
+    match Cached::deserialize(deserializer)? {
+        Cached::Phase(phase) => Ok(phase),
+        Cached::Index(0) => Ok(Phase::Work),
+        Cached::Index(1) => Ok(Phase::ShortBreak),
+        Cached::Index(2) => Ok(Phase::LongBreak),
+        Cached::Index(index) => Err(serde::de::Error::custom(format!(
+            "unknown phase index {index}"
+        ))),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Transition {
+    pub phase: Phase,
+    pub iterations: u8,
+    pub completed: bool,
+}
+
+impl Phase {
+    // the name used in config files and the environment of hooks
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Phase::Work => "work",
+            Phase::ShortBreak => "short_break",
+            Phase::LongBreak => "long_break",
+        }
+    }
+
+    pub fn is_break(&self) -> bool {
+        *self != Phase::Work
+    }
+
+    // works out what comes after this phase
+    // `iterations` is the number of work phases already done in the current set
+    pub fn next(self, iterations: u8, intervals: u8) -> Transition {
+        match self {
+            // the last work phase of a set is followed by a long break
+            Phase::Work if iterations + 1 >= intervals => Transition {
+                phase: Phase::LongBreak,
+                iterations: intervals,
+                completed: false,
+            },
+            Phase::Work => Transition {
+                phase: Phase::ShortBreak,
+                iterations,
+                completed: false,
+            },
+            Phase::ShortBreak => Transition {
+                phase: Phase::Work,
+                iterations: iterations + 1,
+                completed: false,
+            },
+            // since we've gone through a long break, we've also completed a single pomodoro!
+            Phase::LongBreak => Transition {
+                phase: Phase::Work,
+                iterations: 0,
+                completed: true,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_break() {
+        assert!(!Phase::Work.is_break());
+        assert!(Phase::ShortBreak.is_break());
+        assert!(Phase::LongBreak.is_break());
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("work".parse(), Ok(Phase::Work));
+        assert_eq!("break".parse(), Ok(Phase::ShortBreak));
+        assert_eq!("short_break".parse(), Ok(Phase::ShortBreak));
+        assert_eq!("long".parse(), Ok(Phase::LongBreak));
+        assert!("nap".parse::<Phase>().is_err());
+        assert_eq!(Phase::ShortBreak.as_str().parse(), Ok(Phase::ShortBreak));
+    }
+
+    #[test]
+    fn test_next_full_set() {
+        let intervals = 4;
+        let mut phase = Phase::Work;
+        let mut iterations = 0;
+        let mut sequence = vec![];
+
+        for _ in 0..8 {
+            let transition = phase.next(iterations, intervals);
+            phase = transition.phase;
+            iterations = transition.iterations;
+            sequence.push((phase, transition.completed));
+        }
+
+        assert_eq!(
+            sequence,
+            [
+                (Phase::ShortBreak, false),
+                (Phase::Work, false),
+                (Phase::ShortBreak, false),
+                (Phase::Work, false),
+                (Phase::ShortBreak, false),
+                (Phase::Work, false),
+                (Phase::LongBreak, false),
+                (Phase::Work, true),
+            ]
+        );
+        assert_eq!(iterations, 0);
+    }
+
+    #[test]
+    fn test_next_intervals_lowered() {
+        // if the intervals were lowered mid set, the next work phase should still end in a long break
+        let transition = Phase::Work.next(5, 2);
+        assert_eq!(transition.phase, Phase::LongBreak);
+        assert_eq!(transition.iterations, 2);
+    }
+}
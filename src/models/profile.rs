@@ -0,0 +1,11 @@
+// a named set of times, intervals and auto-start flags that can be switched to at runtime
+#[derive(Debug, PartialEq, Clone)]
+pub struct Profile {
+    pub work_time: u64,
+    pub short_break: u64,
+    pub long_break: u64,
+    pub intervals: u8,
+    pub auto_work: bool,
+    pub auto_short_break: bool,
+    pub auto_long_break: bool,
+}
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+
+use crate::utils::helper::{format_duration, parse_time};
+
+use super::phase::Phase;
+
+// a single step of a custom cycle, e.g. a 50 minute work block
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Step {
+    pub name: String,
+    pub phase: Phase,
+    pub duration: u64,
+    pub icon: Option<String>,
+    pub class: Option<String>,
+}
+
+impl Step {
+    pub fn new(phase: Phase, duration: u64) -> Self {
+        Self {
+            name: default_name(phase).to_string(),
+            phase,
+            duration,
+            icon: None,
+            class: None,
+        }
+    }
+}
+
+pub fn default_name(phase: Phase) -> &'static str {
+    match phase {
+        Phase::Work => "work",
+        Phase::ShortBreak => "short break",
+        Phase::LongBreak => "long break",
+    }
+}
+
+// parses the compact form of a sequence, e.g. "work 50m, break 10m, work 50m, long 30m"
+pub fn parse_sequence(input: &str) -> Result<Vec<Step>, String> {
+    let mut steps = vec![];
+    for part in input.split(',') {
+        let mut words = part.split_whitespace();
+        let (Some(phase), Some(duration), None) = (words.next(), words.next(), words.next()) else {
+            return Err(format!(
+                "invalid step \"{}\", expected e.g. \"work 25m\"",
+                part.trim()
+            ));
+        };
+        steps.push(Step::new(phase.parse()?, parse_time(duration)?));
+    }
+    Ok(steps)
+}
+
+// only the phases and durations make it into the compact form
+pub fn format_sequence(steps: &[Step]) -> String {
+    steps
+        .iter()
+        .map(|step| {
+            let phase = match step.phase {
+                Phase::Work => "work",
+                Phase::ShortBreak => "break",
+                Phase::LongBreak => "long",
+            };
+            format!("{phase} {}", format_duration(step.duration))
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sequence() {
+        let steps = parse_sequence("work 50m, break 10m, work 50m, long 30m").unwrap();
+
+        assert_eq!(
+            steps,
+            [
+                Step::new(Phase::Work, 50 * 60),
+                Step::new(Phase::ShortBreak, 10 * 60),
+                Step::new(Phase::Work, 50 * 60),
+                Step::new(Phase::LongBreak, 30 * 60),
+            ]
+        );
+        assert_eq!(steps[1].name, "short break");
+    }
+
+    #[test]
+    fn test_format_sequence() {
+        let input = "work 50m, break 10m, work 1h30m, long 30m";
+        assert_eq!(format_sequence(&parse_sequence(input).unwrap()), input);
+    }
+
+    #[test]
+    fn test_parse_sequence_invalid() {
+        assert_eq!(
+            parse_sequence("work 50m, break"),
+            Err("invalid step \"break\", expected e.g. \"work 25m\"".to_string())
+        );
+        assert!(parse_sequence("nap 10m").is_err());
+        assert!(parse_sequence("work 10x").is_err());
+        assert!(parse_sequence("work 10m 5m").is_err());
+        assert!(parse_sequence("").is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::Shutdown,
+    os::unix::net::UnixStream,
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use serde::Deserialize;
+
+use crate::models::{
+    config::{Config, BAR_OPERATION},
+    output::Output,
+};
+
+use super::{
+    output::{read_clicks, Printer},
+    server::{daemon_socket, send_message_socket, spawn_detached},
+    timer::Timer,
+};
+
+// how long to wait before looking for the daemon again
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+// a line sent by the daemon, see watch::Feed::Bar
+#[derive(Deserialize)]
+struct Frame {
+    text: String,
+    tooltip: String,
+    class: Vec<String>,
+    timer: Timer,
+}
+
+// the daemon, if it's running, takes us on as a bar
+fn connect(binary_name: &str) -> Option<UnixStream> {
+    let mut stream = UnixStream::connect(daemon_socket(binary_name)).ok()?;
+    stream.write_all(BAR_OPERATION.as_bytes()).ok()?;
+    stream.shutdown(Shutdown::Write).ok()?;
+    Some(stream)
+}
+
+// prints every frame the daemon sends until it goes away, failing only when `out` does
+fn follow(
+    stream: UnixStream,
+    printer: &mut Printer,
+    config: &Config,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let Ok(frame) = serde_json::from_str::<Frame>(&line) else {
+            continue;
+        };
+        if let Some(line) = printer.line(
+            frame.text,
+            &frame.tooltip,
+            &frame.class,
+            &frame.timer,
+            config,
+        ) {
+            writeln!(out, "{line}")?;
+            out.flush()?;
+        }
+    }
+    Ok(())
+}
+
+// shows the timer of a daemon, so restarting the bar doesn't lose it. runs until the bar closes stdout
+pub fn run(config: Config, config_file: Option<&str>) {
+    let mut printer = Printer::new(config.output);
+    if let Some(header) = printer.header() {
+        println!("{header}");
+    }
+
+    // clicks are passed on to the daemon
+    if config.output == Output::I3bar {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(|| read_clicks(tx));
+        let socket_path = daemon_socket(&config.binary_name);
+        thread::spawn(move || {
+            for (message, _) in rx {
+                let _ = send_message_socket(&socket_path, &message);
+            }
+        });
+    }
+
+    // shown while there's no daemon to follow
+    let idle = Timer::new(config.work_time, config.short_break, config.long_break, 0);
+    let mut cleared = false;
+    loop {
+        let mut stream = connect(&config.binary_name);
+        if stream.is_none() && config.start_server {
+            match spawn_detached(&config.binary_name, config_file) {
+                Ok(_) => stream = connect(&config.binary_name),
+                Err(e) => eprintln!("err: unable to start the daemon, err == {e}"),
+            }
+        }
+
+        if let Some(stream) = stream {
+            cleared = false;
+            if follow(stream, &mut printer, &config, &mut io::stdout()).is_err() {
+                // the bar is gone
+                return;
+            }
+        }
+        if !cleared {
+            if let Some(line) = printer.line(String::new(), "", &[], &idle, &config) {
+                println!("{line}");
+            }
+            cleared = true;
+        }
+        thread::sleep(RECONNECT_DELAY);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::utils::consts::{LONG_BREAK_TIME, SHORT_BREAK_TIME, WORK_TIME};
+
+    #[test]
+    fn test_follow() {
+        let mut state = Timer::new(WORK_TIME, SHORT_BREAK_TIME, LONG_BREAK_TIME, 0);
+        state.running = true;
+        let frame = json!({
+            "text": "work 25:00",
+            "tooltip": "0 pomodoros completed this session",
+            "class": ["work"],
+            "timer": state,
+        });
+
+        let (server, client) = UnixStream::pair().unwrap();
+        let mut writer = server.try_clone().unwrap();
+        writeln!(writer, "{frame}\nnot a frame\n{frame}").unwrap();
+        server.shutdown(Shutdown::Write).unwrap();
+
+        // returns once the daemon closes the stream, line based bars only print changes
+        let mut out = vec![];
+        let mut printer = Printer::new(Output::Plain);
+        follow(client, &mut printer, &Config::default(), &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "work 25:00\n");
+
+        let (server, client) = UnixStream::pair().unwrap();
+        let mut writer = server.try_clone().unwrap();
+        writeln!(writer, "{frame}").unwrap();
+        server.shutdown(Shutdown::Write).unwrap();
+
+        let mut out = vec![];
+        let mut printer = Printer::new(Output::Waybar);
+        follow(client, &mut printer, &Config::default(), &mut out).unwrap();
+        let message: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(message["text"], "work 25:00");
+        assert_eq!(message["class"], json!(["work"]));
+    }
+}
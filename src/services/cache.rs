@@ -1,27 +1,102 @@
-use std::{env, error::Error, fs::File, io::Write, path::PathBuf};
+use std::{
+    env,
+    error::Error,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
 use crate::models::config::Config;
 
-use super::timer::Timer;
+use super::{
+    events::{Event, Subscriber},
+    timer::Timer,
+};
 
 const MODULE: &str = env!("CARGO_PKG_NAME");
 const VERSION: &str = env!("CARGO_PKG_VERSION");
+const PROFILE_FILE: &str = "profile";
+// how often a running timer is stored, so it can resume about where it was after a crash
+const STORE_INTERVAL: Duration = Duration::from_secs(5);
+
+// keeps the cache up to date while persist is on. transitions are stored right away,
+// the time of a running timer every STORE_INTERVAL, and whatever is left when it's dropped
+pub struct Persister {
+    filepath: PathBuf,
+    // the state last written, nothing is written while it stays the same
+    stored: String,
+    stored_at: Instant,
+    unsaved: Option<String>,
+}
 
-pub fn store(state: &Timer) -> Result<(), Box<dyn Error>> {
-    let mut filepath = cache_dir()?;
-    let output_name = format!("{}-{}", MODULE, VERSION);
-    filepath.push(output_name);
+impl Persister {
+    pub fn new(filepath: PathBuf) -> Self {
+        Self {
+            filepath,
+            stored: String::new(),
+            stored_at: Instant::now(),
+            unsaved: None,
+        }
+    }
+
+    fn store(&mut self, data: String) {
+        if let Err(e) = write(&self.filepath, &data) {
+            eprintln!("err: cache::store, err == {e}");
+        }
+        self.stored = data;
+        self.stored_at = Instant::now();
+        self.unsaved = None;
+    }
+}
+
+impl Subscriber for Persister {
+    fn on_event(&mut self, _event: &Event, state: &Timer, config: &Config) {
+        if config.persist {
+            self.store(serialize(state));
+        }
+    }
+
+    fn on_tick(&mut self, state: &Timer, config: &Config) {
+        if !config.persist {
+            return;
+        }
+        let data = serialize(state);
+        if data == self.stored {
+            self.unsaved = None;
+        } else if self.stored_at.elapsed() >= STORE_INTERVAL {
+            self.store(data);
+        } else {
+            self.unsaved = Some(data);
+        }
+    }
+}
+
+impl Drop for Persister {
+    fn drop(&mut self) {
+        if let Some(data) = self.unsaved.take() {
+            self.store(data);
+        }
+    }
+}
+
+fn serialize(state: &Timer) -> String {
+    serde_json::to_string(&state).expect("Not a serializable type")
+}
 
-    let data = serde_json::to_string(&state).expect("Not a serializable type");
+fn write(filepath: &Path, data: &str) -> Result<(), Box<dyn Error>> {
     Ok(File::create(filepath)?.write_all(data.as_bytes())?)
 }
 
-pub fn restore(state: &mut Timer, config: &Config) -> Result<(), Box<dyn Error>> {
+// where the state of the timer is stored
+pub fn cache_file() -> Result<PathBuf, Box<dyn Error>> {
     let mut filepath = cache_dir()?;
-    let output_name = format!("{}-{}", MODULE, VERSION);
-    filepath.push(output_name);
+    filepath.push(format!("{}-{}", MODULE, VERSION));
+    Ok(filepath)
+}
 
-    let file = File::open(filepath)?;
+pub fn restore(state: &mut Timer, config: &Config) -> Result<(), Box<dyn Error>> {
+    let file = File::open(cache_file()?)?;
     let json: serde_json::Value = serde_json::from_reader(file)?;
     let restored: Timer = match serde_json::from_value(json.clone()) {
         Ok(value) => value,
@@ -29,21 +104,43 @@ pub fn restore(state: &mut Timer, config: &Config) -> Result<(), Box<dyn Error>>
     };
 
     if match_timers(config, &restored.times) {
-        state.current_index = restored.current_index;
+        state.phase = restored.phase;
         state.elapsed_millis = restored.elapsed_millis;
         state.elapsed_time = restored.elapsed_time;
         state.times = restored.times;
+        state.extra_time = restored.extra_time;
+        state.earned_break = restored.earned_break;
+        state.in_overtime = restored.in_overtime;
+        state.countdown = restored.countdown;
+        state.in_warning = restored.in_warning;
         state.iterations = restored.iterations;
         state.session_completed = restored.session_completed;
+        // the phase has to match the step in case the sequence changed since it was stored
+        if let Some(step) = state.sequence.get(restored.step) {
+            state.phase = step.phase;
+            state.step = restored.step;
+        }
     }
 
     Ok(())
 }
 
-fn match_timers(config: &Config, times: &[u16; 3]) -> bool {
-    let work_time: u16 = times[0];
-    let short_break: u16 = times[1];
-    let long_break: u16 = times[2];
+pub fn store_profile(name: &str) -> Result<(), Box<dyn Error>> {
+    let mut filepath = cache_dir()?;
+    filepath.push(PROFILE_FILE);
+    Ok(std::fs::write(filepath, name)?)
+}
+
+pub fn restore_profile() -> Result<String, Box<dyn Error>> {
+    let mut filepath = cache_dir()?;
+    filepath.push(PROFILE_FILE);
+    Ok(std::fs::read_to_string(filepath)?.trim().to_string())
+}
+
+fn match_timers(config: &Config, times: &[u64; 3]) -> bool {
+    let work_time: u64 = times[0];
+    let short_break: u64 = times[1];
+    let long_break: u64 = times[2];
 
     if config.work_time != work_time
         || config.short_break != short_break
@@ -64,7 +161,7 @@ fn cache_dir() -> Result<PathBuf, Box<dyn Error>> {
 
     dir.push(MODULE);
     if let Err(e) = std::fs::create_dir_all(&dir) {
-        println!("create_dir: path == {:?}, err == {e}", dir);
+        eprintln!("create_dir: path == {:?}, err == {e}", dir);
     }
     Ok(dir)
 }
@@ -72,18 +169,19 @@ fn cache_dir() -> Result<PathBuf, Box<dyn Error>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::phase::Phase;
 
     // Mock the env variables used in the code
     const MODULE: &str = "waybar-module-pomodoro";
     const VERSION: &str = "0.1";
 
     fn create_timer(
-        work_time: Option<u16>,
-        short_break: Option<u16>,
-        long_break: Option<u16>,
+        work_time: Option<u64>,
+        short_break: Option<u64>,
+        long_break: Option<u64>,
     ) -> Timer {
         Timer {
-            current_index: 1,
+            phase: Phase::ShortBreak,
             elapsed_millis: 950,
             elapsed_time: 300,
             times: [
@@ -91,10 +189,20 @@ mod tests {
                 short_break.unwrap_or(5),
                 long_break.unwrap_or(15),
             ],
+            extra_time: 0,
+            earned_break: None,
+            in_overtime: false,
+            countdown: 0,
+            in_warning: false,
             iterations: 2,
             session_completed: 8,
             running: false,
             socket_nr: 0,
+            sequence: vec![],
+            step: 0,
+            events: vec![],
+            away_since: None,
+            running_away: false,
         }
     }
 
@@ -106,7 +214,7 @@ mod tests {
         }
 
         let timer = create_timer(None, None, None);
-        store(&timer)?;
+        write(&cache_file()?, &serialize(&timer))?;
         let mut restored_timer = create_timer(Some(30), Some(10), Some(20));
 
         let config = Config {
@@ -118,7 +226,7 @@ mod tests {
 
         restore(&mut restored_timer, &config)?;
 
-        assert_eq!(restored_timer.current_index, timer.current_index);
+        assert_eq!(restored_timer.phase, timer.phase);
         assert_eq!(restored_timer.elapsed_millis, timer.elapsed_millis);
         assert_eq!(restored_timer.elapsed_time, timer.elapsed_time);
         assert_eq!(restored_timer.times, timer.times);
@@ -136,7 +244,7 @@ mod tests {
         }
 
         let timer = create_timer(None, None, None);
-        store(&timer)?;
+        write(&cache_file()?, &serialize(&timer))?;
         let mut restored_timer = create_timer(Some(30), Some(10), Some(20));
 
         let config = Config {
@@ -149,7 +257,7 @@ mod tests {
         restore(&mut restored_timer, &config)?;
 
         // Check if the restored timer state is not changed
-        assert_eq!(restored_timer.current_index, 1);
+        assert_eq!(restored_timer.phase, Phase::ShortBreak);
         assert_eq!(restored_timer.elapsed_millis, 950);
         assert_eq!(restored_timer.elapsed_time, 300);
         assert_eq!(restored_timer.times, [30, 10, 20]);
@@ -159,6 +267,53 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_restore_old_cache() {
+        // as stored before phases had names and before any of the later fields existed
+        let json = r#"{"current_index":2,"elapsed_millis":400,"elapsed_time":60,"times":[1500,300,900],"iterations":4,"session_completed":3,"running":true,"socket_nr":0}"#;
+        let timer: Timer = serde_json::from_str(json).unwrap();
+
+        assert_eq!(timer.phase, Phase::LongBreak);
+        assert_eq!(timer.elapsed_time, 60);
+        assert_eq!(timer.session_completed, 3);
+        assert_eq!(timer.step, 0);
+        assert!(!timer.in_overtime);
+
+        let json = json.replace(r#""current_index":2"#, r#""current_index":7"#);
+        assert!(serde_json::from_str::<Timer>(&json).is_err());
+    }
+
+    #[test]
+    fn test_persister() -> Result<(), Box<dyn Error>> {
+        let filepath = env::temp_dir().join("waybar-module-pomodoro_test_persister");
+        let _ = std::fs::remove_file(&filepath);
+        let config = Config {
+            persist: true,
+            ..Default::default()
+        };
+        let mut timer = create_timer(None, None, None);
+        let mut persister = Persister::new(filepath.clone());
+
+        // the time of a running timer isn't written on every tick
+        timer.running = true;
+        persister.on_tick(&timer, &config);
+        assert!(!filepath.exists());
+
+        // transitions are
+        persister.on_event(&Event::Resumed, &timer, &config);
+        assert_eq!(std::fs::read_to_string(&filepath)?, serialize(&timer));
+
+        // and whatever wasn't written yet is on exit
+        timer.elapsed_time += 1;
+        persister.on_tick(&timer, &config);
+        drop(persister);
+        let stored: Timer = serde_json::from_str(&std::fs::read_to_string(&filepath)?)?;
+        assert_eq!(stored.elapsed_time, timer.elapsed_time);
+
+        std::fs::remove_file(filepath)?;
+        Ok(())
+    }
+
     #[test]
     fn test_cache_dir_creation() -> Result<(), Box<dyn Error>> {
         unsafe {
@@ -169,7 +324,7 @@ mod tests {
         let mut dir = dirs::cache_dir().expect("unable to get cache dir");
         dir.push(MODULE);
         if let Err(e) = std::fs::create_dir(&dir) {
-            println!("err: err == {e}");
+            eprintln!("err: err == {e}");
         }
 
         let result = cache_dir()?;
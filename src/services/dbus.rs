@@ -0,0 +1,348 @@
+use std::sync::{Arc, Mutex};
+
+use zbus::{
+    blocking::{connection::Builder, Connection},
+    fdo, interface, SignalContext,
+};
+
+use crate::models::{config::Config, message::Message, phase::Phase};
+
+use super::{
+    events::{Event, Subscriber},
+    server::broadcast_message,
+    timer::Timer,
+};
+
+pub const BUS_NAME: &str = "org.waybar.Pomodoro";
+pub const OBJECT_PATH: &str = "/org/waybar/Pomodoro";
+
+// the state of the timer as seen on the bus
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Properties {
+    phase: String,
+    remaining: u64,
+    running: bool,
+    completed: u32,
+}
+
+impl Properties {
+    fn new(state: &Timer) -> Self {
+        Self {
+            phase: state.phase.as_str().to_string(),
+            remaining: state.get_remaining_time(),
+            running: state.running,
+            completed: state.session_completed,
+        }
+    }
+}
+
+struct Pomodoro {
+    // methods are sent to every instance of the module like any other client would
+    binary_name: String,
+    // shared with the service, so calls that wait on the server don't hold up its updates
+    properties: Arc<Mutex<Properties>>,
+}
+
+impl Pomodoro {
+    fn send(&self, message: &str) -> fdo::Result<()> {
+        match broadcast_message(&self.binary_name, message) {
+            // the server only turns down what it's given, e.g. a duration that's too long
+            Ok(reply) if reply.starts_with("err: ") => {
+                Err(fdo::Error::InvalidArgs(reply[5..].to_string()))
+            }
+            Ok(_) => Ok(()),
+            Err(e) => Err(fdo::Error::Failed(e.to_string())),
+        }
+    }
+
+    fn properties(&self) -> Properties {
+        self.properties.lock().unwrap().clone()
+    }
+}
+
+#[interface(name = "org.waybar.Pomodoro")]
+impl Pomodoro {
+    fn start(&self) -> fdo::Result<()> {
+        self.send("start")
+    }
+
+    fn stop(&self) -> fdo::Result<()> {
+        self.send("stop")
+    }
+
+    fn toggle(&self) -> fdo::Result<()> {
+        self.send("toggle")
+    }
+
+    fn reset(&self) -> fdo::Result<()> {
+        self.send("reset")
+    }
+
+    fn skip(&self) -> fdo::Result<()> {
+        self.send("skip")
+    }
+
+    // `phase` is work, short_break or long_break, `seconds` its new length
+    fn set_duration(&self, phase: &str, seconds: u64) -> fdo::Result<()> {
+        let name = match phase.parse().map_err(fdo::Error::InvalidArgs)? {
+            Phase::Work => "set-work",
+            Phase::ShortBreak => "set-short",
+            Phase::LongBreak => "set-long",
+        };
+        self.send(&Message::new(name, &format!("{seconds}s")).encode())
+    }
+
+    #[zbus(property)]
+    fn phase(&self) -> String {
+        self.properties().phase
+    }
+
+    #[zbus(property)]
+    fn remaining(&self) -> u64 {
+        self.properties().remaining
+    }
+
+    #[zbus(property)]
+    fn running(&self) -> bool {
+        self.properties().running
+    }
+
+    #[zbus(property)]
+    fn completed(&self) -> u32 {
+        self.properties().completed
+    }
+
+    // the property change signals already take the name PhaseChanged would get
+    #[zbus(signal, name = "PhaseChanged")]
+    async fn phase_started(ctxt: &SignalContext<'_>, phase: &str) -> zbus::Result<()>;
+}
+
+pub struct DbusService {
+    connection: Connection,
+    properties: Arc<Mutex<Properties>>,
+}
+
+impl DbusService {
+    // takes the bus to connect to, which is the session bus outside of tests
+    pub fn connect(builder: Builder, binary_name: &str) -> zbus::Result<Self> {
+        let properties = Arc::new(Mutex::new(Properties::default()));
+        let pomodoro = Pomodoro {
+            binary_name: binary_name.to_string(),
+            properties: properties.clone(),
+        };
+        let connection = builder
+            .name(BUS_NAME)?
+            .serve_at(OBJECT_PATH, pomodoro)?
+            .build()?;
+
+        Ok(Self {
+            connection,
+            properties,
+        })
+    }
+
+    fn update(&self, state: &Timer) -> zbus::Result<()> {
+        let new = Properties::new(state);
+        let old = std::mem::replace(&mut *self.properties.lock().unwrap(), new.clone());
+        if old == new {
+            return Ok(());
+        }
+
+        let iface = self
+            .connection
+            .object_server()
+            .interface::<_, Pomodoro>(OBJECT_PATH)?;
+        let pomodoro = iface.get();
+        let ctxt = iface.signal_context();
+        zbus::block_on(async {
+            if old.phase != new.phase {
+                pomodoro.phase_changed(ctxt).await?;
+            }
+            if old.remaining != new.remaining {
+                pomodoro.remaining_changed(ctxt).await?;
+            }
+            if old.running != new.running {
+                pomodoro.running_changed(ctxt).await?;
+            }
+            if old.completed != new.completed {
+                pomodoro.completed_changed(ctxt).await?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl Subscriber for DbusService {
+    fn on_event(&mut self, event: &Event, _state: &Timer, _config: &Config) {
+        if let Event::PhaseStarted { phase } = event {
+            let ctxt = SignalContext::new(self.connection.inner(), OBJECT_PATH)
+                .expect("object path is valid");
+            if let Err(e) = zbus::block_on(Pomodoro::phase_started(&ctxt, phase.as_str())) {
+                eprintln!("err: unable to emit PhaseChanged, err == {e}");
+            }
+        }
+    }
+
+    fn on_tick(&mut self, state: &Timer, _config: &Config) {
+        if let Err(e) = self.update(state) {
+            eprintln!("err: unable to update dbus properties, err == {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod testing {
+    use std::{
+        io::{BufRead, BufReader},
+        process::{Child, Command, Stdio},
+    };
+
+    use zbus::blocking::connection::Builder;
+
+    // a bus of our own, so the tests neither need nor touch the session bus
+    pub struct PrivateBus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl PrivateBus {
+        pub fn start() -> Option<Self> {
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()?;
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.as_mut()?)
+                .read_line(&mut address)
+                .ok()?;
+            Some(Self {
+                daemon,
+                address: address.trim().to_string(),
+            })
+        }
+
+        pub fn builder(&self) -> Builder<'_> {
+            Builder::address(self.address.as_str()).unwrap()
+        }
+    }
+
+    impl Drop for PrivateBus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        os::unix::net::UnixListener,
+        sync::mpsc,
+        thread,
+        time::Duration,
+    };
+
+    use zbus::{blocking::proxy::Builder as ProxyBuilder, CacheProperties};
+
+    use super::{testing::PrivateBus, *};
+    use crate::utils::consts::{LONG_BREAK_TIME, SHORT_BREAK_TIME, WORK_TIME};
+
+    // stands in for the server, passing on every message it receives and turning down zero durations
+    fn listen(socket_path: &str) -> mpsc::Receiver<String> {
+        let _ = std::fs::remove_file(socket_path);
+        let listener = UnixListener::bind(socket_path).unwrap();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut message = String::new();
+                let _ = stream.read_to_string(&mut message);
+                if message.ends_with(";0s]") {
+                    let _ = stream.write_all(b"err: too short");
+                }
+                if tx.send(message).is_err() {
+                    return;
+                }
+            }
+        });
+        rx
+    }
+
+    #[test]
+    fn test_dbus_service() {
+        let Some(bus) = PrivateBus::start() else {
+            println!("dbus-daemon not available, skipping");
+            return;
+        };
+        let binary_name = "waybar-module-pomodoro_test_dbus";
+        let socket_path = std::env::temp_dir()
+            .join(format!("{binary_name}0.socket"))
+            .to_string_lossy()
+            .to_string();
+        let messages = listen(&socket_path);
+
+        let mut service = DbusService::connect(bus.builder(), binary_name).unwrap();
+        let client = bus.builder().build().unwrap();
+        let proxy: zbus::blocking::Proxy = ProxyBuilder::new(&client)
+            .destination(BUS_NAME)
+            .unwrap()
+            .path(OBJECT_PATH)
+            .unwrap()
+            .interface(BUS_NAME)
+            .unwrap()
+            .cache_properties(CacheProperties::No)
+            .build()
+            .unwrap();
+
+        let timeout = Duration::from_secs(5);
+        proxy.call_method("Toggle", &()).unwrap();
+        assert_eq!(messages.recv_timeout(timeout).unwrap(), "toggle");
+        proxy
+            .call_method("SetDuration", &("short_break", 90u64))
+            .unwrap();
+        assert_eq!(
+            messages.recv_timeout(timeout).unwrap(),
+            Message::new("set-short", "90s").encode()
+        );
+        assert!(proxy.call_method("SetDuration", &("nap", 90u64)).is_err());
+        let Err(zbus::Error::MethodError(name, _, _)) =
+            proxy.call_method("SetDuration", &("work", 0u64))
+        else {
+            panic!("a zero duration was accepted");
+        };
+        assert_eq!(name.as_str(), "org.freedesktop.DBus.Error.InvalidArgs");
+        assert_eq!(
+            messages.recv_timeout(timeout).unwrap(),
+            Message::new("set-work", "0s").encode()
+        );
+
+        let mut state = Timer::new(WORK_TIME, SHORT_BREAK_TIME, LONG_BREAK_TIME, 0);
+        state.phase = Phase::LongBreak;
+        state.session_completed = 4;
+        let mut signals = proxy.receive_signal("PhaseChanged").unwrap();
+        service.on_event(
+            &Event::PhaseStarted {
+                phase: Phase::LongBreak,
+            },
+            &state,
+            &Config::default(),
+        );
+        service.on_tick(&state, &Config::default());
+
+        let signal = signals.next().unwrap();
+        assert_eq!(signal.body().deserialize::<&str>().unwrap(), "long_break");
+
+        assert_eq!(proxy.get_property::<String>("Phase").unwrap(), "long_break");
+        assert_eq!(
+            proxy.get_property::<u64>("Remaining").unwrap(),
+            LONG_BREAK_TIME
+        );
+        assert!(!proxy.get_property::<bool>("Running").unwrap());
+        assert_eq!(proxy.get_property::<u32>("Completed").unwrap(), 4);
+
+        let _ = std::fs::remove_file(socket_path);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::models::{config::Config, phase::Phase};
+
+use super::timer::Timer;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Event {
+    PhaseStarted {
+        phase: Phase,
+    },
+    PhaseCompleted {
+        phase: Phase,
+        elapsed: u64,
+        planned: u64,
+    },
+    Skipped {
+        phase: Phase,
+        elapsed: u64,
+        planned: u64,
+    },
+    // the phase ran out but keeps counting until acknowledged
+    OvertimeStarted {
+        phase: Phase,
+    },
+    // the phase is about to run out, see Config::warning
+    WarningStarted {
+        phase: Phase,
+        remaining: u64,
+    },
+    Paused,
+    Resumed,
+    Reset,
+}
+
+pub trait Subscriber {
+    fn on_event(&mut self, event: &Event, state: &Timer, config: &Config);
+
+    // called on every update of the module, for anything that follows the timer as it runs
+    fn on_tick(&mut self, _state: &Timer, _config: &Config) {}
+}
+
+#[derive(Default)]
+pub struct Dispatcher {
+    subscribers: Vec<Box<dyn Subscriber>>,
+}
+
+impl Dispatcher {
+    pub fn subscribe(&mut self, subscriber: Box<dyn Subscriber>) {
+        self.subscribers.push(subscriber);
+    }
+
+    pub fn dispatch(&mut self, events: Vec<Event>, state: &Timer, config: &Config) {
+        for event in events.iter() {
+            for subscriber in self.subscribers.iter_mut() {
+                subscriber.on_event(event, state, config);
+            }
+        }
+    }
+
+    pub fn tick(&mut self, state: &Timer, config: &Config) {
+        for subscriber in self.subscribers.iter_mut() {
+            subscriber.on_tick(state, config);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+
+    struct Recorder(Rc<RefCell<Vec<Event>>>);
+
+    impl Subscriber for Recorder {
+        fn on_event(&mut self, event: &Event, _state: &Timer, _config: &Config) {
+            self.0.borrow_mut().push(*event);
+        }
+    }
+
+    struct TickCounter(Rc<RefCell<u32>>);
+
+    impl Subscriber for TickCounter {
+        fn on_event(&mut self, _event: &Event, _state: &Timer, _config: &Config) {}
+
+        fn on_tick(&mut self, _state: &Timer, _config: &Config) {
+            *self.0.borrow_mut() += 1;
+        }
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let received = Rc::new(RefCell::new(vec![]));
+        let mut dispatcher = Dispatcher::default();
+        dispatcher.subscribe(Box::new(Recorder(received.clone())));
+        dispatcher.subscribe(Box::new(Recorder(received.clone())));
+
+        let state = Timer::new(1, 1, 1, 0);
+        dispatcher.dispatch(
+            vec![Event::Resumed, Event::Paused],
+            &state,
+            &Config::default(),
+        );
+
+        assert_eq!(
+            *received.borrow(),
+            [Event::Resumed, Event::Resumed, Event::Paused, Event::Paused]
+        );
+    }
+
+    #[test]
+    fn test_tick() {
+        let ticks = Rc::new(RefCell::new(0));
+        let received = Rc::new(RefCell::new(vec![]));
+        let mut dispatcher = Dispatcher::default();
+        dispatcher.subscribe(Box::new(TickCounter(ticks.clone())));
+        dispatcher.subscribe(Box::new(Recorder(received.clone())));
+
+        let state = Timer::new(1, 1, 1, 0);
+        dispatcher.tick(&state, &Config::default());
+        dispatcher.tick(&state, &Config::default());
+
+        assert_eq!(*ticks.borrow(), 2);
+        assert!(received.borrow().is_empty());
+    }
+}
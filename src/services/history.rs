@@ -0,0 +1,174 @@
+use std::{
+    error::Error,
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{config::Config, phase::Phase};
+
+use super::{
+    events::{Event, Subscriber},
+    timer::Timer,
+};
+
+const MODULE: &str = env!("CARGO_PKG_NAME");
+const HISTORY_FILE: &str = "history.jsonl";
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Entry {
+    pub cycle: Phase,
+    pub ended_at: u64,
+    pub elapsed: u64,
+    pub planned: u64,
+    pub skipped: bool,
+    // time spent past the planned time before the cycle was acknowledged
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub overtime: u64,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+impl Entry {
+    pub fn new(cycle: Phase, elapsed: u64, planned: u64, skipped: bool) -> Self {
+        Self {
+            cycle,
+            ended_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            elapsed,
+            planned,
+            skipped,
+            overtime: elapsed.saturating_sub(planned),
+        }
+    }
+}
+
+pub struct HistoryRecorder;
+
+impl Subscriber for HistoryRecorder {
+    fn on_event(&mut self, event: &Event, state: &Timer, config: &Config) {
+        // same as notifications, only the first instance should write to the history
+        if !config.history || state.socket_nr != 0 {
+            return;
+        }
+
+        let entry = match *event {
+            Event::PhaseCompleted {
+                phase,
+                elapsed,
+                planned,
+            } => Entry::new(phase, elapsed, planned, false),
+            Event::Skipped {
+                phase,
+                elapsed,
+                planned,
+            } => Entry::new(phase, elapsed, planned, true),
+            _ => return,
+        };
+
+        if let Err(e) = record(&entry) {
+            eprintln!("err: history::record, err == {e}");
+        }
+    }
+}
+
+pub fn record(entry: &Entry) -> Result<(), Box<dyn Error>> {
+    append(&history_path()?, entry)
+}
+
+fn append(filepath: &Path, entry: &Entry) -> Result<(), Box<dyn Error>> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(filepath)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+fn history_path() -> Result<PathBuf, Box<dyn Error>> {
+    let mut dir = if let Some(dir) = dirs::data_dir() {
+        dir
+    } else {
+        return Err("unable to get data dir".into());
+    };
+
+    dir.push(MODULE);
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        eprintln!("create_dir: path == {:?}, err == {e}", dir);
+    }
+    dir.push(HISTORY_FILE);
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        fs::File,
+        io::{BufRead, BufReader},
+    };
+
+    fn load(filepath: &Path) -> Result<Vec<Entry>, Box<dyn Error>> {
+        let reader = BufReader::new(File::open(filepath)?);
+        let mut entries = vec![];
+        for line in reader.lines() {
+            entries.push(serde_json::from_str(&line?)?);
+        }
+        Ok(entries)
+    }
+
+    #[test]
+    fn test_append_and_load() -> Result<(), Box<dyn Error>> {
+        let filepath = std::env::temp_dir().join("waybar-module-pomodoro_test_history.jsonl");
+        let _ = std::fs::remove_file(&filepath);
+
+        append(&filepath, &Entry::new(Phase::Work, 1500, 1500, false))?;
+        append(&filepath, &Entry::new(Phase::ShortBreak, 60, 300, true))?;
+
+        let entries = load(&filepath)?;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].cycle, Phase::Work);
+        assert!(!entries[0].skipped);
+        assert_eq!(entries[1].cycle, Phase::ShortBreak);
+        assert_eq!(entries[1].elapsed, 60);
+        assert_eq!(entries[1].planned, 300);
+        assert!(entries[1].skipped);
+        assert_eq!(entries[1].overtime, 0);
+
+        std::fs::remove_file(filepath)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_entry_overtime() {
+        let entry = Entry::new(Phase::Work, 1700, 1500, false);
+        assert_eq!(entry.overtime, 200);
+        assert!(serde_json::to_string(&entry)
+            .unwrap()
+            .ends_with(",\"skipped\":false,\"overtime\":200}"));
+    }
+
+    #[test]
+    fn test_entry_serialization() {
+        let entry = Entry {
+            cycle: Phase::LongBreak,
+            ended_at: 42,
+            elapsed: 900,
+            planned: 900,
+            skipped: false,
+            overtime: 0,
+        };
+
+        assert_eq!(
+            serde_json::to_string(&entry).unwrap(),
+            "{\"cycle\":\"long_break\",\"ended_at\":42,\"elapsed\":900,\"planned\":900,\"skipped\":false}"
+        );
+    }
+}
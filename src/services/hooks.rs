@@ -0,0 +1,195 @@
+use std::{
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    models::{config::Config, phase::Phase},
+    utils::consts::SLEEP_DURATION,
+};
+
+use super::{
+    events::{Event, Subscriber},
+    timer::Timer,
+};
+
+pub struct HookRunner;
+
+impl Subscriber for HookRunner {
+    fn on_event(&mut self, event: &Event, state: &Timer, config: &Config) {
+        // same as notifications, hooks only run for the first instance of the module
+        if state.socket_nr != 0 {
+            return;
+        }
+
+        if let Some((name, command)) = hook(event, config) {
+            run(
+                command,
+                &environment(name, event, state, config),
+                config.hook_timeout,
+            );
+        }
+    }
+}
+
+// the name and command of the hook for an event, if one is set
+fn hook<'a>(event: &Event, config: &'a Config) -> Option<(&'static str, &'a str)> {
+    let (name, command) = match event {
+        Event::PhaseStarted { phase: Phase::Work } => ("work_start", &config.on_work_start),
+        Event::PhaseStarted {
+            phase: Phase::ShortBreak,
+        } => ("break_start", &config.on_break_start),
+        Event::PhaseStarted {
+            phase: Phase::LongBreak,
+        } => ("long_break_start", &config.on_long_break_start),
+        Event::Paused => ("pause", &config.on_pause),
+        Event::Resumed => ("resume", &config.on_resume),
+        Event::Reset => ("reset", &config.on_reset),
+        Event::PhaseCompleted { .. } => ("complete", &config.on_complete),
+        _ => return None,
+    };
+    command
+        .as_deref()
+        .filter(|command| !command.trim().is_empty())
+        .map(|command| (name, command))
+}
+
+fn environment(
+    name: &str,
+    event: &Event,
+    state: &Timer,
+    config: &Config,
+) -> Vec<(&'static str, String)> {
+    let mut env = vec![
+        ("POMODORO_EVENT", name.to_string()),
+        ("POMODORO_PHASE", state.phase.as_str().to_string()),
+        ("POMODORO_REMAINING", state.get_remaining_time().to_string()),
+        ("POMODORO_RUNNING", state.running.to_string()),
+        ("POMODORO_ITERATION", state.iterations.to_string()),
+        ("POMODORO_COMPLETED", state.session_completed.to_string()),
+        (
+            "POMODORO_PROFILE",
+            config.profile.clone().unwrap_or_default(),
+        ),
+    ];
+
+    // the phase that just ended, rather than the one that follows it
+    if let Event::PhaseCompleted {
+        phase,
+        elapsed,
+        planned,
+    } = event
+    {
+        env.push(("POMODORO_COMPLETED_PHASE", phase.as_str().to_string()));
+        env.push(("POMODORO_ELAPSED", elapsed.to_string()));
+        env.push(("POMODORO_PLANNED", planned.to_string()));
+    }
+    env
+}
+
+// hooks run in the background so they can't hold up the timer, and are killed if they take too long
+fn run(command: &str, env: &[(&str, String)], timeout: u64) {
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        // stdout is where the module talks to waybar
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .spawn();
+
+    match child {
+        Ok(child) => {
+            let command = command.to_string();
+            thread::spawn(move || wait(child, &command, Duration::from_secs(timeout)));
+        }
+        Err(e) => eprintln!("err: unable to run hook, command == {command}, err == {e}"),
+    }
+}
+
+fn wait(mut child: Child, command: &str, timeout: Duration) {
+    let start = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => return,
+            Ok(None) if start.elapsed() >= timeout => {
+                eprintln!("err: hook timed out, command == {command}");
+                let _ = child.kill();
+                let _ = child.wait();
+                return;
+            }
+            Ok(None) => thread::sleep(SLEEP_DURATION),
+            Err(e) => {
+                eprintln!("err: hook, command == {command}, err == {e}");
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::consts::{LONG_BREAK_TIME, SHORT_BREAK_TIME, WORK_TIME};
+
+    #[test]
+    fn test_hook() {
+        let config = Config {
+            on_work_start: Some("focus".to_string()),
+            on_long_break_start: Some(" ".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            hook(&Event::PhaseStarted { phase: Phase::Work }, &config),
+            Some(("work_start", "focus"))
+        );
+        assert_eq!(
+            hook(
+                &Event::PhaseStarted {
+                    phase: Phase::LongBreak
+                },
+                &config
+            ),
+            None
+        );
+        assert_eq!(hook(&Event::Paused, &config), None);
+    }
+
+    #[test]
+    fn test_environment() {
+        let mut state = Timer::new(WORK_TIME, SHORT_BREAK_TIME, LONG_BREAK_TIME, 0);
+        state.phase = Phase::ShortBreak;
+        let event = Event::PhaseCompleted {
+            phase: Phase::Work,
+            elapsed: 1500,
+            planned: 1500,
+        };
+
+        let env = environment("complete", &event, &state, &Config::default());
+        assert!(env.contains(&("POMODORO_EVENT", "complete".to_string())));
+        assert!(env.contains(&("POMODORO_PHASE", "short_break".to_string())));
+        assert!(env.contains(&("POMODORO_COMPLETED_PHASE", "work".to_string())));
+        assert!(env.contains(&("POMODORO_ELAPSED", "1500".to_string())));
+    }
+
+    #[test]
+    fn test_run() {
+        let filepath = std::env::temp_dir().join("waybar-module-pomodoro_test_hook");
+        let _ = std::fs::remove_file(&filepath);
+
+        run(
+            &format!("echo $POMODORO_PHASE > {}", filepath.display()),
+            &[("POMODORO_PHASE", "work".to_string())],
+            5,
+        );
+
+        let start = Instant::now();
+        while std::fs::read_to_string(&filepath).unwrap_or_default() != "work\n" {
+            assert!(start.elapsed() < Duration::from_secs(5), "hook didn't run");
+            thread::sleep(SLEEP_DURATION);
+        }
+        std::fs::remove_file(filepath).unwrap();
+    }
+}
@@ -0,0 +1,318 @@
+use std::{
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use zbus::{
+    blocking::{fdo::DBusProxy, proxy::Builder, Connection, Proxy},
+    CacheProperties,
+};
+
+use crate::models::{config::Config, media::PauseMedia};
+
+use super::{
+    events::{Event, Subscriber},
+    timer::Timer,
+};
+
+const PLAYER_PREFIX: &str = "org.mpris.MediaPlayer2.";
+const PLAYER_PATH: &str = "/org/mpris/MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+// what the worker is asked to do, in the order of the events
+enum Request {
+    // the players to pause, all of them when empty
+    Pause(Vec<String>),
+    Play,
+}
+
+#[derive(Default)]
+pub struct MediaController {
+    // started on first use, along with the connection to the session bus
+    worker: Option<Sender<Request>>,
+    // whether the last request was to pause, so there may be players to play again
+    pausing: bool,
+}
+
+impl MediaController {
+    fn worker(&mut self) -> zbus::Result<&Sender<Request>> {
+        if self.worker.is_none() {
+            let connection = Connection::session()?;
+            let (tx, rx) = mpsc::channel();
+            // players can be slow to answer, which mustn't hold up the timer
+            thread::spawn(move || work(&connection, rx));
+            self.worker = Some(tx);
+        }
+        Ok(self.worker.as_ref().unwrap())
+    }
+}
+
+// handles requests one after the other until the controller is dropped
+fn work(connection: &Connection, requests: Receiver<Request>) {
+    // the players paused by us, only these are played again
+    let mut paused: Vec<String> = vec![];
+    for request in requests {
+        match request {
+            Request::Play => {
+                play_players(connection, &paused);
+                paused.clear();
+            }
+            Request::Pause(players) => match pause_players(connection, &players) {
+                Ok(names) => {
+                    for name in names {
+                        if !paused.contains(&name) {
+                            paused.push(name);
+                        }
+                    }
+                }
+                Err(e) => eprintln!("err: unable to pause media players, err == {e}"),
+            },
+        }
+    }
+}
+
+// whether players should be paused after the event, none if it changes nothing
+fn should_pause(event: &Event, state: &Timer, config: &Config) -> Option<bool> {
+    match event {
+        Event::PhaseStarted { phase } => Some(config.pause_media.pauses(*phase)),
+        Event::Resumed => Some(config.pause_media.pauses(state.phase)),
+        // the phase is over early or on hold, so it no longer keeps the players paused.
+        // being away is the exception, nobody's there to listen
+        Event::Reset => Some(false),
+        Event::Paused if state.away_since.is_none() => Some(false),
+        _ => None,
+    }
+}
+
+impl Subscriber for MediaController {
+    fn on_event(&mut self, event: &Event, state: &Timer, config: &Config) {
+        // like notifications, only the first instance of the module controls players
+        if state.socket_nr != 0 {
+            return;
+        }
+        let Some(pause) = should_pause(event, state, config) else {
+            return;
+        };
+        // players paused before this was turned off are still played again
+        if config.pause_media == PauseMedia::Off && !self.pausing {
+            return;
+        }
+
+        let request = if pause {
+            Request::Pause(config.media_players.clone())
+        } else {
+            Request::Play
+        };
+        match self.worker() {
+            Ok(worker) => {
+                let _ = worker.send(request);
+                self.pausing = pause;
+            }
+            Err(e) => eprintln!("err: unable to connect to the session bus, err == {e}"),
+        }
+    }
+}
+
+// whether the player is one of `players`, e.g. "firefox" matches org.mpris.MediaPlayer2.firefox.instance_1_42
+fn is_player(name: &str, players: &[String]) -> bool {
+    let Some(instance) = name.strip_prefix(PLAYER_PREFIX) else {
+        return false;
+    };
+    players.is_empty()
+        || players.iter().any(|player| {
+            instance == player
+                || instance
+                    .strip_prefix(player.as_str())
+                    .is_some_and(|rest| rest.starts_with('.'))
+        })
+}
+
+fn player<'a>(connection: &Connection, name: &'a str) -> zbus::Result<Proxy<'a>> {
+    Builder::new(connection)
+        .destination(name)?
+        .path(PLAYER_PATH)?
+        .interface(PLAYER_INTERFACE)?
+        .cache_properties(CacheProperties::No)
+        .build()
+}
+
+fn has_status(player: &Proxy, status: &str) -> bool {
+    player
+        .get_property::<String>("PlaybackStatus")
+        .is_ok_and(|current| current == status)
+}
+
+// pauses the players that are playing, returning their names
+fn pause_players(connection: &Connection, players: &[String]) -> zbus::Result<Vec<String>> {
+    let mut paused = vec![];
+    for name in DBusProxy::new(connection)?.list_names()? {
+        if !is_player(name.as_str(), players) {
+            continue;
+        }
+        let player = player(connection, name.as_str())?;
+        if has_status(&player, "Playing") {
+            match player.call_method("Pause", &()) {
+                Ok(_) => paused.push(name.to_string()),
+                Err(e) => eprintln!("err: unable to pause {name}, err == {e}"),
+            }
+        }
+    }
+    Ok(paused)
+}
+
+// plays the players again, unless they were stopped or closed in the meantime
+fn play_players(connection: &Connection, names: &[String]) {
+    for name in names {
+        let result = player(connection, name).and_then(|player| {
+            if has_status(&player, "Paused") {
+                player.call_method("Play", &())?;
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            eprintln!("err: unable to play {name}, err == {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zbus::interface;
+
+    use super::*;
+    use crate::models::phase::Phase;
+    use crate::services::dbus::testing::PrivateBus;
+
+    struct FakePlayer {
+        status: String,
+    }
+
+    #[interface(name = "org.mpris.MediaPlayer2.Player")]
+    impl FakePlayer {
+        fn play(&mut self) {
+            self.status = "Playing".to_string();
+        }
+
+        fn pause(&mut self) {
+            self.status = "Paused".to_string();
+        }
+
+        #[zbus(property)]
+        fn playback_status(&self) -> String {
+            self.status.clone()
+        }
+    }
+
+    fn status(connection: &Connection, name: &str) -> String {
+        player(connection, name)
+            .unwrap()
+            .get_property("PlaybackStatus")
+            .unwrap()
+    }
+
+    #[test]
+    fn test_should_pause() {
+        let config = Config {
+            pause_media: PauseMedia::Break,
+            ..Default::default()
+        };
+        let mut state = Timer::new(1500, 300, 900, 0);
+        state.phase = Phase::ShortBreak;
+        let started = Event::PhaseStarted {
+            phase: Phase::ShortBreak,
+        };
+
+        assert_eq!(should_pause(&started, &state, &config), Some(true));
+        assert_eq!(should_pause(&Event::Resumed, &state, &config), Some(true));
+        assert_eq!(should_pause(&Event::Paused, &state, &config), Some(false));
+        assert_eq!(should_pause(&Event::Reset, &state, &config), Some(false));
+        let skipped = Event::Skipped {
+            phase: Phase::ShortBreak,
+            elapsed: 10,
+            planned: 300,
+        };
+        assert_eq!(should_pause(&skipped, &state, &config), None);
+
+        // locking the screen pauses the timer, but the players stay quiet
+        state.away(&config);
+        assert_eq!(should_pause(&Event::Paused, &state, &config), None);
+    }
+
+    #[test]
+    fn test_is_player() {
+        let players = vec!["firefox".to_string(), "mpv".to_string()];
+
+        assert!(is_player("org.mpris.MediaPlayer2.mpv", &players));
+        assert!(is_player(
+            "org.mpris.MediaPlayer2.firefox.instance_1_42",
+            &players
+        ));
+        assert!(!is_player("org.mpris.MediaPlayer2.mpvd", &players));
+        assert!(!is_player("org.mpris.MediaPlayer2.spotify", &players));
+        assert!(is_player("org.mpris.MediaPlayer2.spotify", &[]));
+        assert!(!is_player("org.freedesktop.Notifications", &[]));
+    }
+
+    #[test]
+    fn test_pause_and_play_players() {
+        let Some(bus) = PrivateBus::start() else {
+            println!("dbus-daemon not available, skipping");
+            return;
+        };
+        let players: Vec<_> = [
+            ("mpv", "Playing"),
+            ("spotify", "Playing"),
+            ("vlc", "Paused"),
+        ]
+        .into_iter()
+        .map(|(name, status)| {
+            bus.builder()
+                .name(format!("{PLAYER_PREFIX}{name}"))
+                .unwrap()
+                .serve_at(
+                    PLAYER_PATH,
+                    FakePlayer {
+                        status: status.to_string(),
+                    },
+                )
+                .unwrap()
+                .build()
+                .unwrap()
+        })
+        .collect();
+        let connection = bus.builder().build().unwrap();
+        let mpv = format!("{PLAYER_PREFIX}mpv");
+        let spotify = format!("{PLAYER_PREFIX}spotify");
+        let vlc = format!("{PLAYER_PREFIX}vlc");
+
+        let paused = pause_players(&connection, &["mpv".to_string()]).unwrap();
+        assert_eq!(paused, vec![mpv.clone()]);
+        assert_eq!(status(&connection, &mpv), "Paused");
+        assert_eq!(status(&connection, &spotify), "Playing");
+
+        // vlc was already paused, so it's left alone
+        let paused = pause_players(&connection, &[]).unwrap();
+        assert_eq!(paused, vec![spotify.clone()]);
+
+        play_players(&connection, std::slice::from_ref(&mpv));
+        assert_eq!(status(&connection, &mpv), "Playing");
+        assert_eq!(status(&connection, &spotify), "Paused");
+        assert_eq!(status(&connection, &vlc), "Paused");
+
+        // requests sent right after each other are handled in order, only what was paused is played
+        let (tx, rx) = mpsc::channel();
+        tx.send(Request::Pause(vec!["mpv".to_string()])).unwrap();
+        tx.send(Request::Play).unwrap();
+        tx.send(Request::Play).unwrap();
+        drop(tx);
+        let worker = {
+            let connection = connection.clone();
+            thread::spawn(move || work(&connection, rx))
+        };
+        worker.join().unwrap();
+        assert_eq!(status(&connection, &mpv), "Playing");
+        assert_eq!(status(&connection, &spotify), "Paused");
+
+        drop(players);
+    }
+}
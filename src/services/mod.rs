@@ -1,3 +1,14 @@
+pub mod bar;
 pub mod cache;
+pub mod dbus;
+pub mod events;
+pub mod history;
+pub mod hooks;
+pub mod media;
+pub mod notification;
+pub mod output;
 pub mod server;
+pub mod session;
+pub mod template;
 pub mod timer;
+pub mod watch;
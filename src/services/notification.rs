@@ -0,0 +1,403 @@
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use notify_rust::{handle_action, ActionResponse, Hint, Notification, Timeout};
+
+use crate::{
+    models::{
+        config::Config, message::Message, notification::NotificationConfig, phase::Phase,
+        sequence::default_name,
+    },
+    utils::helper::{format_duration, trim_whitespace},
+};
+
+use super::{
+    events::{Event, Subscriber},
+    server::broadcast_message,
+    template::{placeholders, render},
+    timer::Timer,
+};
+
+const BUS: &str = "org.freedesktop.Notifications";
+const OBJECT_PATH: &str = "/org/freedesktop/Notifications";
+
+// buttons on a notification, as pairs of the command to send and its label
+type Actions = Vec<(String, &'static str)>;
+
+pub struct Notifier {
+    // the commands behind the action buttons are sent to every instance of it
+    binary_name: String,
+    // the notification on screen, the next one replaces it instead of stacking up
+    id: Option<u32>,
+    // the notification whose buttons are being listened for, there's only ever one listener
+    listening: Arc<Mutex<Option<u32>>>,
+    // with a live notification, the last message and its buttons stay above the remaining time
+    message: Option<(String, Actions)>,
+    live_text: String,
+}
+
+impl Notifier {
+    pub fn new(binary_name: &str) -> Self {
+        Self {
+            binary_name: binary_name.to_string(),
+            id: None,
+            listening: Arc::new(Mutex::new(None)),
+            message: None,
+            live_text: String::new(),
+        }
+    }
+
+    fn show(
+        &mut self,
+        settings: &NotificationConfig,
+        body: &str,
+        actions: &[(String, &'static str)],
+        persistent: bool,
+        quiet: bool,
+    ) {
+        let mut notification = Notification::new();
+        notification.summary(&settings.summary).body(body);
+        if let Some(id) = self.id {
+            notification.id(id);
+        }
+        if let Some(icon) = &settings.icon {
+            notification.icon(icon);
+        }
+        if let Some(urgency) = settings.urgency {
+            notification.urgency(urgency);
+        }
+        if persistent {
+            notification
+                .timeout(Timeout::Never)
+                .hint(Hint::Resident(true));
+        } else if let Some(timeout) = settings.timeout {
+            notification.timeout(Duration::from_secs(timeout));
+        }
+        if let Some(category) = &settings.category {
+            notification.hint(Hint::Category(category.clone()));
+        }
+        if let (Some(sound), false) = (&settings.sound, quiet) {
+            notification.sound_name(sound);
+        }
+        for (command, label) in actions {
+            notification.action(command, label);
+        }
+
+        match notification.show() {
+            Ok(handle) => self.id = Some(handle.id()),
+            Err(e) => {
+                eprintln!("err: send_notification, err == {e}");
+                return;
+            }
+        }
+        if !actions.is_empty() {
+            self.listen();
+        }
+    }
+
+    fn show_live(&mut self, settings: &NotificationConfig, quiet: bool) {
+        let (body, actions) = match &self.message {
+            Some((message, actions)) => (format!("{message}\n{}", self.live_text), actions.clone()),
+            None => (self.live_text.clone(), vec![]),
+        };
+        self.show(settings, &body, &actions, true, quiet);
+    }
+
+    // waiting for a click blocks until the notification is closed, so it gets its own thread
+    fn listen(&self) {
+        let Some(id) = self.id else {
+            return;
+        };
+        let mut listening = self.listening.lock().unwrap();
+        if *listening == Some(id) {
+            return;
+        }
+        *listening = Some(id);
+
+        let listening = self.listening.clone();
+        let binary_name = self.binary_name.clone();
+        thread::spawn(move || {
+            handle_action(id, |response| {
+                if let ActionResponse::Custom(action) = response {
+                    if let Err(e) = broadcast_message(&binary_name, action) {
+                        eprintln!("err: notification action, err == {e}");
+                    }
+                }
+            });
+
+            let mut listening = listening.lock().unwrap();
+            if *listening == Some(id) {
+                *listening = None;
+            }
+        });
+    }
+
+    fn close(&mut self) {
+        self.message = None;
+        self.live_text.clear();
+        if let Some(id) = self.id.take() {
+            // it may well be gone already, which is fine
+            let _ = close_notification(id);
+        }
+    }
+}
+
+impl Subscriber for Notifier {
+    fn on_event(&mut self, event: &Event, state: &Timer, config: &Config) {
+        // only send a notification for the first instance of the module
+        if state.socket_nr != 0 {
+            return;
+        }
+
+        // the user is back, so there's nothing left to notify them about
+        if *event == Event::Resumed {
+            if config.live_notification {
+                self.message = None;
+                self.live_text.clear();
+            } else {
+                self.close();
+            }
+            return;
+        }
+
+        let Some((settings, body)) = content(event, state, config) else {
+            return;
+        };
+        let actions = actions(event, state, config);
+        if config.live_notification {
+            self.message = Some((body, actions));
+            self.live_text = live_text(state, config);
+            self.show_live(settings, false);
+        } else {
+            self.show(settings, &body, &actions, false, false);
+        }
+    }
+
+    fn on_tick(&mut self, state: &Timer, config: &Config) {
+        if state.socket_nr != 0 {
+            return;
+        }
+
+        if !config.live_notification {
+            // it was turned off at runtime
+            if !self.live_text.is_empty() {
+                self.close();
+            }
+            return;
+        }
+
+        let text = live_text(state, config);
+        if text != self.live_text {
+            self.live_text = text;
+            self.show_live(config.notification(state.phase), true);
+        }
+    }
+}
+
+impl Drop for Notifier {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+// the live notification shows the same text as the bar
+fn live_text(state: &Timer, config: &Config) -> String {
+    trim_whitespace(&render(&config.format, &placeholders(state, config)))
+}
+
+fn close_notification(id: u32) -> zbus::Result<()> {
+    let connection = zbus::blocking::Connection::session()?;
+    connection.call_method(
+        Some(BUS),
+        OBJECT_PATH,
+        Some(BUS),
+        "CloseNotification",
+        &(id),
+    )?;
+    Ok(())
+}
+
+// the settings and body of the notification for an event, if it should get one
+fn content<'a>(
+    event: &Event,
+    state: &Timer,
+    config: &'a Config,
+) -> Option<(&'a NotificationConfig, String)> {
+    let (settings, body) = match event {
+        Event::PhaseStarted { phase } => {
+            let settings = config.notification(*phase);
+            let body = match (&settings.body, state.current_step()) {
+                (Some(body), _) => render(body, &placeholders(state, config)),
+                (None, Some(step)) => format!("Time for {}!", step.name),
+                (None, None) => message(*phase).to_string(),
+            };
+            (settings, body)
+        }
+        // overtime is about the phase that's up next, so it looks like its notification
+        Event::OvertimeStarted { phase } => {
+            let name = match state.current_step() {
+                Some(step) => step.name.as_str(),
+                None => default_name(*phase),
+            };
+            (
+                config.notification(state.upcoming_phase(config)),
+                format!("Time's up, {name} is now in overtime!"),
+            )
+        }
+        // warnings are turned off by not setting one, so they don't check whether they're enabled
+        Event::WarningStarted { phase, remaining } => {
+            let name = match state.current_step() {
+                Some(step) => step.name.as_str(),
+                None => default_name(*phase),
+            };
+            return Some((
+                config.notification(*phase),
+                format!("Only {} left of {name}!", format_duration(*remaining)),
+            ));
+        }
+        _ => return None,
+    };
+
+    settings.enabled.then_some((settings, body))
+}
+
+// buttons to add to the notification for an event
+fn actions(event: &Event, state: &Timer, config: &Config) -> Actions {
+    let mut actions = vec![];
+    match event {
+        Event::PhaseStarted { phase } if !state.running => {
+            actions.push(("start".to_string(), start_label(*phase)));
+        }
+        // starting from overtime moves on to the next phase
+        Event::OvertimeStarted { .. } => {
+            actions.push((
+                "start".to_string(),
+                start_label(state.upcoming_phase(config)),
+            ));
+        }
+        Event::PhaseStarted { .. } | Event::WarningStarted { .. } => (),
+        _ => return actions,
+    }
+
+    actions.push(("skip".to_string(), "Skip"));
+    actions.push((Message::new("add", "5m").encode(), "+5 min"));
+    actions
+}
+
+fn start_label(phase: Phase) -> &'static str {
+    match phase {
+        Phase::Work => "Start work",
+        Phase::ShortBreak | Phase::LongBreak => "Start break",
+    }
+}
+
+fn message(phase: Phase) -> &'static str {
+    match phase {
+        Phase::Work => "Time to work!",
+        Phase::ShortBreak => "Time for a short break!",
+        Phase::LongBreak => "Time for a long break!",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::consts::{LONG_BREAK_TIME, SHORT_BREAK_TIME, WORK_TIME};
+
+    fn commands(actions: Vec<(String, &str)>) -> Vec<String> {
+        actions.into_iter().map(|(command, _)| command).collect()
+    }
+
+    #[test]
+    fn test_send_notification_work() {
+        Notifier::new("").show(&Default::default(), message(Phase::Work), &[], false, false);
+    }
+
+    #[test]
+    fn test_send_notification_short_break() {
+        Notifier::new("").show(
+            &Default::default(),
+            message(Phase::ShortBreak),
+            &[],
+            false,
+            false,
+        );
+    }
+
+    #[test]
+    fn test_send_notification_long_break() {
+        Notifier::new("").show(
+            &Default::default(),
+            message(Phase::LongBreak),
+            &[],
+            false,
+            false,
+        );
+    }
+
+    #[test]
+    fn test_actions() {
+        let mut state = Timer::new(WORK_TIME, SHORT_BREAK_TIME, LONG_BREAK_TIME, 0);
+        let started = Event::PhaseStarted {
+            phase: Phase::ShortBreak,
+        };
+
+        let config = Config::default();
+        let buttons = actions(&started, &state, &config);
+        assert_eq!(buttons[0], ("start".to_string(), "Start break"));
+        assert_eq!(commands(buttons)[1..], ["skip", "[add;5m]"]);
+
+        // there's nothing to start if it started by itself
+        state.running = true;
+        assert_eq!(
+            commands(actions(&started, &state, &config)),
+            ["skip", "[add;5m]"]
+        );
+
+        let overtime = Event::OvertimeStarted {
+            phase: Phase::LongBreak,
+        };
+        state.phase = Phase::LongBreak;
+        assert_eq!(actions(&overtime, &state, &config)[0].1, "Start work");
+
+        assert!(actions(&Event::Paused, &state, &config).is_empty());
+    }
+
+    #[test]
+    fn test_content() {
+        let mut state = Timer::new(WORK_TIME, SHORT_BREAK_TIME, LONG_BREAK_TIME, 0);
+        let mut config = Config::default();
+        let started = Event::PhaseStarted { phase: Phase::Work };
+
+        let (settings, body) = content(&started, &state, &config).unwrap();
+        assert_eq!(settings.summary, "Pomodoro");
+        assert_eq!(body, "Time to work!");
+
+        config.notifications[Phase::Work as usize].body =
+            Some("{phase} for {remaining}, {completed} done".to_string());
+        state.session_completed = 2;
+        let (_, body) = content(&started, &state, &config).unwrap();
+        assert_eq!(body, "work for 25:00, 2 done");
+
+        config.notifications[Phase::Work as usize].enabled = false;
+        assert_eq!(content(&started, &state, &config), None);
+
+        // overtime in a work phase looks like the break that follows it
+        config.notifications[Phase::ShortBreak as usize].summary = "Break".to_string();
+        let overtime = Event::OvertimeStarted { phase: Phase::Work };
+        let (settings, body) = content(&overtime, &state, &config).unwrap();
+        assert_eq!(settings.summary, "Break");
+        assert_eq!(body, "Time's up, work is now in overtime!");
+
+        // warnings show even with notifications for the phase turned off
+        let warning = Event::WarningStarted {
+            phase: Phase::Work,
+            remaining: 90,
+        };
+        let (_, body) = content(&warning, &state, &config).unwrap();
+        assert_eq!(body, "Only 1m30s left of work!");
+    }
+}
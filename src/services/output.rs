@@ -0,0 +1,315 @@
+use std::{
+    io::{self, BufRead},
+    sync::mpsc::{self, Sender},
+};
+
+use serde_json::{json, Value};
+
+use crate::models::{config::Config, output::Output};
+
+use super::timer::Timer;
+
+// the name of the block in the i3bar protocol, click events carry it back
+const BLOCK_NAME: &str = "pomodoro";
+
+// turns each update of the module into what the bar expects on stdout
+pub struct Printer {
+    output: Output,
+    // i3bar wants a comma between the updates of its endless array
+    first: bool,
+    // line based bars only get a new line when something changed
+    last: Option<String>,
+}
+
+impl Printer {
+    pub fn new(output: Output) -> Self {
+        Self {
+            output,
+            first: true,
+            last: None,
+        }
+    }
+
+    // printed once, before the first update
+    pub fn header(&self) -> Option<String> {
+        match self.output {
+            Output::Waybar | Output::Plain | Output::Polybar | Output::Lemonbar | Output::None => {
+                None
+            }
+            Output::I3bar => Some(format!(
+                "{}\n[",
+                json!({ "version": 1, "click_events": true })
+            )),
+        }
+    }
+
+    // the line to print for an update, if any
+    pub fn line(
+        &mut self,
+        text: String,
+        tooltip: &str,
+        class: &[String],
+        state: &Timer,
+        config: &Config,
+    ) -> Option<String> {
+        let line = match self.output {
+            Output::None => return None,
+            Output::Waybar => create_message(text, tooltip, class),
+            Output::I3bar => {
+                let separator = if self.first { "" } else { "," };
+                format!("{separator}[{}]", create_block(text, state, config))
+            }
+            Output::Plain => text,
+            Output::Polybar | Output::Lemonbar => create_tags(&text, state, config),
+        };
+        self.first = false;
+
+        if matches!(self.output, Output::Waybar | Output::I3bar) {
+            return Some(line);
+        }
+        if self.last.as_ref() == Some(&line) {
+            return None;
+        }
+        self.last = Some(line.clone());
+        Some(line)
+    }
+}
+
+pub fn tooltip(state: &Timer) -> String {
+    format!(
+        "{} pomodoro{} completed this session",
+        state.session_completed,
+        if state.session_completed > 1 || state.session_completed == 0 {
+            "s"
+        } else {
+            ""
+        }
+    )
+}
+
+// the text can contain anything from the config, e.g. quotes in a profile or step name
+fn create_message(value: String, tooltip: &str, class: &[String]) -> String {
+    json!({
+        "text": value,
+        "tooltip": tooltip,
+        "class": class,
+        "alt": "",
+    })
+    .to_string()
+}
+
+fn color<'a>(state: &Timer, config: &'a Config) -> &'a str {
+    if !state.running {
+        &config.pause_color
+    } else if state.is_break() {
+        &config.break_color
+    } else {
+        &config.work_color
+    }
+}
+
+fn create_block(text: String, state: &Timer, config: &Config) -> Value {
+    let color = color(state, config);
+
+    let mut block = json!({
+        "name": BLOCK_NAME,
+        "full_text": text,
+        // a cycle that's about to run out or past it wants attention
+        "urgent": state.in_warning || state.in_overtime,
+    });
+    if !color.is_empty() {
+        block["color"] = json!(color);
+    }
+    block
+}
+
+// polybar and lemonbar share their formatting tags, polybar runs the commands of the clicks itself
+// while lemonbar prints them, to be piped into a shell
+fn create_tags(text: &str, state: &Timer, config: &Config) -> String {
+    let mut result = text.to_string();
+    for (button, operation) in [(1, "toggle"), (2, "skip"), (3, "reset")] {
+        // colons end the command, unless escaped
+        let command = format!("{} {operation}", config.binary_name).replace(':', "\\:");
+        result = format!("%{{A{button}:{command}:}}{result}%{{A}}");
+    }
+
+    let color = color(state, config);
+    if !color.is_empty() {
+        result = format!("%{{F{color}}}{result}%{{F-}}");
+    }
+    result
+}
+
+// what clicking the block does, from a click event sent by i3bar
+fn click_command(line: &str) -> Option<&'static str> {
+    // the events are the elements of an endless array, so each but the first starts with a comma
+    let event = line.trim().trim_start_matches(['[', ',']);
+    let event: Value = serde_json::from_str(event).ok()?;
+    if event["name"] != BLOCK_NAME {
+        return None;
+    }
+
+    match event["button"].as_u64()? {
+        1 => Some("toggle"),
+        2 => Some("skip"),
+        3 => Some("reset"),
+        _ => None,
+    }
+}
+
+// passes clicks on the block on to the timer, for as long as i3bar keeps stdin open
+pub fn read_clicks(tx: Sender<(String, Sender<String>)>) {
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        if let Some(command) = click_command(&line) {
+            let (reply_tx, _reply_rx) = mpsc::channel();
+            if tx.send((command.to_string(), reply_tx)).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::phase::Phase;
+    use crate::utils::consts::{LONG_BREAK_TIME, SHORT_BREAK_TIME, WORK_TIME};
+
+    fn create_timer() -> Timer {
+        Timer::new(WORK_TIME, SHORT_BREAK_TIME, LONG_BREAK_TIME, 0)
+    }
+
+    #[test]
+    fn test_create_message() {
+        let message = "Pomodoro";
+        let tooltip = "Tooltip";
+        let class = vec!["Class".to_owned()];
+
+        let result: Value =
+            serde_json::from_str(&create_message(message.to_string(), tooltip, &class)).unwrap();
+        assert_eq!(result["text"], message);
+        assert_eq!(result["tooltip"], tooltip);
+        assert_eq!(result["class"], json!(["Class"]));
+        assert_eq!(result["alt"], "");
+
+        // quotes and backslashes from e.g. a profile name mustn't break the line
+        let message = r#"[say "hi" \o/] 25:00"#;
+        let result: Value =
+            serde_json::from_str(&create_message(message.to_string(), tooltip, &class)).unwrap();
+        assert_eq!(result["text"], message);
+    }
+
+    #[test]
+    fn test_i3bar() {
+        let mut printer = Printer::new(Output::I3bar);
+        let config = Config::default();
+        let mut state = create_timer();
+
+        let header = printer.header().unwrap();
+        let (version, array) = header.split_once('\n').unwrap();
+        let version: Value = serde_json::from_str(version).unwrap();
+        assert_eq!(version["version"], 1);
+        assert_eq!(version["click_events"], true);
+        assert_eq!(array, "[");
+
+        let line = printer
+            .line("▶ 25:00".to_string(), "", &[], &state, &config)
+            .unwrap();
+        let blocks: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(blocks[0]["full_text"], "▶ 25:00");
+        assert_eq!(blocks[0]["color"], config.pause_color.as_str());
+        assert_eq!(blocks[0]["urgent"], false);
+
+        state.running = true;
+        state.in_warning = true;
+        let line = printer
+            .line("\"quoted\"".to_string(), "", &[], &state, &config)
+            .unwrap();
+        let blocks: Value = serde_json::from_str(line.strip_prefix(',').unwrap()).unwrap();
+        assert_eq!(blocks[0]["full_text"], "\"quoted\"");
+        assert_eq!(blocks[0]["color"], config.work_color.as_str());
+        assert_eq!(blocks[0]["urgent"], true);
+    }
+
+    #[test]
+    fn test_plain() {
+        let mut printer = Printer::new(Output::Plain);
+        assert_eq!(printer.header(), None);
+
+        let state = create_timer();
+        let config = Config::default();
+        let line = printer.line("▶ 25:00".to_string(), "", &[], &state, &config);
+        assert_eq!(line, Some("▶ 25:00".to_string()));
+
+        // nothing changed, so nothing to print
+        let line = printer.line("▶ 25:00".to_string(), "", &[], &state, &config);
+        assert_eq!(line, None);
+    }
+
+    #[test]
+    fn test_polybar() {
+        let mut printer = Printer::new(Output::Polybar);
+        let config = Config {
+            binary_name: "waybar-module-pomodoro".to_string(),
+            ..Default::default()
+        };
+        let mut state = create_timer();
+        state.running = true;
+        state.phase = Phase::ShortBreak;
+
+        let line = printer
+            .line("05:00".to_string(), "", &[], &state, &config)
+            .unwrap();
+        assert_eq!(
+            line,
+            format!(
+                "%{{F{}}}%{{A3:waybar-module-pomodoro reset:}}%{{A2:waybar-module-pomodoro skip:}}\
+                 %{{A1:waybar-module-pomodoro toggle:}}05:00%{{A}}%{{A}}%{{A}}%{{F-}}",
+                config.break_color
+            )
+        );
+
+        let config = Config {
+            binary_name: "pomo:dev".to_string(),
+            break_color: String::new(),
+            ..config
+        };
+        let line = printer
+            .line("05:00".to_string(), "", &[], &state, &config)
+            .unwrap();
+        assert!(line.starts_with("%{A3:pomo\\:dev reset:}"));
+        assert!(line.ends_with("%{A}"));
+    }
+
+    #[test]
+    fn test_none() {
+        let mut printer = Printer::new(Output::None);
+        assert_eq!(printer.header(), None);
+
+        let line = printer.line(
+            "▶ 25:00".to_string(),
+            "",
+            &[],
+            &create_timer(),
+            &Config::default(),
+        );
+        assert_eq!(line, None);
+    }
+
+    #[test]
+    fn test_click_command() {
+        assert_eq!(
+            click_command(r#"[{"name":"pomodoro","button":1,"x":10}"#),
+            Some("toggle")
+        );
+        assert_eq!(
+            click_command(r#",{"name":"pomodoro","button":3}"#),
+            Some("reset")
+        );
+        assert_eq!(click_command(r#",{"name":"clock","button":1}"#), None);
+        assert_eq!(click_command("["), None);
+    }
+}
@@ -1,105 +1,237 @@
 use std::{
     env, fs,
-    io::{Error, Read, Write},
-    os::unix::net::{UnixListener, UnixStream},
+    io::{BufRead, BufReader, Error, ErrorKind, Read, Write},
+    net::Shutdown,
+    os::unix::{
+        net::{UnixListener, UnixStream},
+        process::CommandExt,
+    },
     path::Path,
-    sync::mpsc::{Receiver, Sender},
+    process::{Command, Stdio},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
     thread,
+    time::Duration,
 };
 
-use notify_rust::Notification;
-
 use crate::{
-    models::{config::Config, message::Message},
-    utils::{
-        self,
-        consts::{HOUR, MINUTE, SLEEP_DURATION},
+    models::{
+        config::{
+            Config, BAR_OPERATION, DAEMON_OPERATION, PROFILE_OPERATION, SAVE_OPERATION,
+            STATUS_OPERATION, WATCH_OPERATION,
+        },
+        config_file,
+        message::Message,
+        output::Output,
+        phase::Phase,
     },
+    utils::{self, consts::SLEEP_DURATION, helper::parse_time},
 };
 
 use super::{
-    cache,
-    timer::{CycleType, Timer},
+    cache::{self, Persister},
+    dbus::DbusService,
+    events::Dispatcher,
+    history::HistoryRecorder,
+    hooks::HookRunner,
+    media::MediaController,
+    notification::Notifier,
+    output::{read_clicks, tooltip, Printer},
+    session,
+    template::{placeholders, render},
+    timer::Timer,
+    watch::{Feed, Watchers},
 };
 
-pub fn send_notification(cycle_type: CycleType) {
-    if let Err(e) = Notification::new()
-        .summary("Pomodoro")
-        .body(match cycle_type {
-            CycleType::Work => "Time to work!",
-            CycleType::ShortBreak => "Time for a short break!",
-            CycleType::LongBreak => "Time for a long break!",
-        })
-        .show()
-    {
-        println!("err: send_notification, err == {e}");
+// replies sent back to the client, to be printed by it
+type Reply = Option<String>;
+
+fn switch_profile(state: &mut Timer, config: &mut Config, name: &str) -> Result<(), String> {
+    config.apply_profile(name)?;
+
+    state.set_times([config.work_time, config.short_break, config.long_break]);
+    if config.persist {
+        if let Err(e) = cache::store_profile(name) {
+            eprintln!("err: cache::store_profile, err == {e}");
+        }
     }
+    Ok(())
 }
 
-fn format_time(elapsed_time: u16, max_time: u16) -> String {
-    let time = max_time - elapsed_time;
+fn set_option(state: &mut Timer, config: &mut Config, input: &str) -> Result<(), String> {
+    let (key, value) = input.split_once(' ').unwrap_or((input, ""));
+    if key == PROFILE_OPERATION {
+        return switch_profile(state, config, value);
+    }
 
-    let hour = time / HOUR;
-    let minute = (time % HOUR) / MINUTE;
-    let second = time % MINUTE;
+    config.set(key, value)?;
 
-    if hour > 0 {
-        return format!("{:02}:{:02}:{:02}", hour, minute, second);
+    // the timer keeps its own copy of the times and sequence
+    match key {
+        "work_time" => state.set_time(Phase::Work, config.work_time),
+        "short_break" => state.set_time(Phase::ShortBreak, config.short_break),
+        "long_break" => state.set_time(Phase::LongBreak, config.long_break),
+        "sequence" => state.set_sequence(config.sequence.clone()),
+        _ => (),
     }
+    Ok(())
+}
 
-    format!("{:02}:{:02}", minute, second)
+fn save_option(config: &Config, key: &str) -> Result<(), String> {
+    let Some(filepath) = &config.config_file else {
+        return Err("no config file to save to".to_string());
+    };
+    config_file::save(filepath, key, config)
+        .map_err(|e| format!("unable to save to {}, {e}", filepath.display()))
 }
 
-fn create_message(value: String, tooltip: &str, class: &[String]) -> String {
-    let class_json = format!(
-        "[{}]",
-        class
-            .iter()
-            .map(|c| format!("\"{}\"", c))
-            .collect::<Vec<String>>()
-            .join(",")
-    );
+fn to_reply(result: Result<(), String>) -> Reply {
+    result.err().map(|e| format!("err: {e}"))
+}
 
-    format!(
-        "{{\"text\": \"{}\", \"tooltip\": \"{}\", \"class\": {}, \"alt\": \"{}\"}}",
-        value, tooltip, class_json, ""
-    )
+// what's needed to answer status right away, instead of waiting for the next update of the timer
+#[derive(Default)]
+struct Status {
+    format: String,
+    placeholders: Vec<(&'static str, String)>,
 }
 
-fn process_message(state: &mut Timer, message: &str) {
+impl Status {
+    // the text of the module as it shows up in the bar, or in the format sent along with status
+    fn reply(&self, message: &str) -> String {
+        let format = match Message::decode(message) {
+            Ok(msg) => msg.value().to_string(),
+            Err(_) => self.format.clone(),
+        };
+        utils::helper::trim_whitespace(&render(&format, &self.placeholders))
+    }
+}
+
+fn is_status(message: &str) -> bool {
+    message == STATUS_OPERATION
+        || Message::decode(message).is_ok_and(|msg| msg.name() == STATUS_OPERATION)
+}
+
+fn process_message(state: &mut Timer, message: &str, config: &mut Config) -> Reply {
     if let Ok(msg) = Message::decode(message) {
         match msg.name() {
-            "set-work" => state.set_time(CycleType::Work, msg.value() as u16),
-            "set-short" => state.set_time(CycleType::ShortBreak, msg.value() as u16),
-            "set-long" => state.set_time(CycleType::LongBreak, msg.value() as u16),
-            _ => println!("err: invalid command, {}", msg.name()),
+            PROFILE_OPERATION => return to_reply(switch_profile(state, config, msg.value())),
+            "set" => return to_reply(set_option(state, config, msg.value())),
+            SAVE_OPERATION => return to_reply(save_option(config, msg.value())),
+            "get" => {
+                return Some(match config.get(msg.value()) {
+                    Ok(value) => value.to_string(),
+                    Err(e) => format!("err: {e}"),
+                })
+            }
+            _ => (),
+        }
+
+        let seconds = match parse_time(msg.value()) {
+            Ok(seconds) => seconds,
+            Err(e) => return Some(format!("err: invalid value for {}, {e}", msg.name())),
+        };
+
+        // the same as setting the option, so get and --save see the new time
+        let key = match msg.name() {
+            "set-work" => Some("work_time"),
+            "set-short" => Some("short_break"),
+            "set-long" => Some("long_break"),
+            _ => None,
+        };
+        if let Some(key) = key {
+            return to_reply(set_option(state, config, &format!("{key} {}", msg.value())));
+        }
+
+        match msg.name() {
+            "add" => state.add_time(seconds),
+            "sub" => state.sub_time(seconds),
+            _ => eprintln!("err: invalid command, {}", msg.name()),
         }
     } else {
         match message {
+            // overtime lasts until one of these, which moves on to the next phase
+            "start" | "toggle" if state.in_overtime => {
+                state.acknowledge(config, true);
+            }
+            "stop" | "skip" | "break" if state.in_overtime => {
+                state.acknowledge(config, false);
+            }
             "start" => {
-                state.running = true;
+                state.start();
+            }
+            // stopping flowtime work is how the break is earned
+            "stop" if state.counts_up(config) => {
+                state.take_break(config);
             }
             "stop" => {
-                state.running = false;
+                state.stop();
             }
             "toggle" => {
-                state.running = !state.running;
+                state.toggle();
             }
             "reset" => {
                 state.reset();
             }
+            "skip" => {
+                state.skip(config);
+            }
+            "break" => {
+                state.take_break(config);
+            }
+            // sent by the session watcher, not meant to be used directly
+            "away" => {
+                state.away(config);
+            }
+            "back" => {
+                state.back(config);
+            }
             _ => {
-                println!("Unknown message: {}", message);
+                eprintln!("Unknown message: {}", message);
             }
         }
     }
+    None
+}
+
+// sends away and back to every instance, for as long as the server runs
+fn watch_session(binary_name: String) {
+    thread::spawn(move || {
+        let result = zbus::blocking::Connection::system()
+            .and_then(|connection| session::watch(&connection, &binary_name));
+        if let Err(e) = result {
+            eprintln!("err: unable to follow the session on logind, err == {e}");
+        }
+    });
 }
 
-fn handle_client(rx: Receiver<String>, socket_path: String, config: Config) {
-    let socket_nr = socket_path
-        .chars()
-        .filter_map(|c| c.to_digit(10))
-        .fold(0, |acc, digit| acc * 10 + digit) as i32;
+fn handle_client(
+    rx: Receiver<(String, Sender<String>)>,
+    socket_path: String,
+    mut config: Config,
+    watchers: Arc<Mutex<Vec<(UnixStream, Feed)>>>,
+    status: Arc<Mutex<Status>>,
+) {
+    let socket_nr = instance_number(&socket_path, &config.binary_name);
+
+    // the header has to come before anything else is printed
+    let mut printer = Printer::new(config.output);
+    if let Some(header) = printer.header() {
+        println!("{header}");
+    }
+
+    // the last profile switched to wins over the one set in the config file
+    let profile = cache::restore_profile()
+        .ok()
+        .filter(|name| config.persist && config.profiles.contains_key(name))
+        .or(config.profile.clone());
+    if let Some(name) = profile {
+        if let Err(e) = config.apply_profile(&name) {
+            eprintln!("err: {e}");
+        }
+    }
 
     let mut state = Timer::new(
         config.work_time,
@@ -108,49 +240,76 @@ fn handle_client(rx: Receiver<String>, socket_path: String, config: Config) {
         socket_nr,
     );
 
+    state.set_sequence(config.sequence.clone());
+
     if config.persist {
         let _ = cache::restore(&mut state, &config);
     }
 
+    let mut dispatcher = Dispatcher::default();
+    dispatcher.subscribe(Box::new(Notifier::new(&config.binary_name)));
+    if let Ok(filepath) = cache::cache_file() {
+        dispatcher.subscribe(Box::new(Persister::new(filepath)));
+    }
+    dispatcher.subscribe(Box::new(HistoryRecorder));
+    dispatcher.subscribe(Box::new(HookRunner));
+    dispatcher.subscribe(Box::new(MediaController::default()));
+    dispatcher.subscribe(Box::new(Watchers::new(watchers)));
+    // there can only be one owner of the bus name
+    if socket_nr == 0 {
+        match zbus::blocking::connection::Builder::session()
+            .and_then(|builder| DbusService::connect(builder, &config.binary_name))
+        {
+            Ok(service) => dispatcher.subscribe(Box::new(service)),
+            Err(e) => eprintln!("err: unable to start the dbus service, err == {e}"),
+        }
+    }
+
+    let mut watching_session = false;
     loop {
-        if let Ok(message) = rx.try_recv() {
-            process_message(&mut state, &message);
+        // logind is only followed once something needs it, by one instance for all of them.
+        // checked every time, since set can turn it on at runtime
+        if socket_nr == 0 && !watching_session && (config.pause_on_lock || config.idle_reset > 0) {
+            watch_session(config.binary_name.clone());
+            watching_session = true;
         }
 
-        let value = format_time(state.elapsed_time, state.get_current_time());
-        let value_prefix = config.get_play_pause_icon(state.running);
-        let tooltip = format!(
-            "{} pomodoro{} completed this session",
-            state.session_completed,
-            if state.session_completed > 1 || state.session_completed == 0 {
-                "s"
-            } else {
-                ""
+        if let Ok((message, reply)) = rx.try_recv() {
+            // the subscribers get a chance to clean up, before the server is told it can exit
+            if message == "exit" {
+                drop(dispatcher);
+                let _ = reply.send(String::new());
+                return;
             }
-        );
+            let response = process_message(&mut state, &message, &mut config);
+            let _ = reply.send(response.unwrap_or_default());
+        }
+
+        let placeholders = placeholders(&state, &config);
+        let text = render(&config.format, &placeholders);
+        *status.lock().unwrap() = Status {
+            format: config.format.clone(),
+            placeholders,
+        };
+        let tooltip = tooltip(&state);
         let class = state.get_class();
-        let cycle_icon = config.get_cycle_icon(state.is_break());
         state.update_state(&config);
-        println!(
-            "{}",
-            create_message(
-                utils::helper::trim_whitespace(&format!(
-                    "{} {} {}",
-                    value_prefix, value, cycle_icon
-                )),
-                tooltip.as_str(),
-                &class,
-            )
-        );
+        dispatcher.dispatch(state.take_events(), &state, &config);
+        dispatcher.tick(&state, &config);
+        if let Some(line) = printer.line(
+            utils::helper::trim_whitespace(&text),
+            tooltip.as_str(),
+            &class,
+            &state,
+            &config,
+        ) {
+            println!("{line}");
+        }
 
         if state.running {
             state.increment_time();
         }
 
-        if config.persist {
-            let _ = cache::store(&state);
-        }
-
         std::thread::sleep(SLEEP_DURATION);
     }
 }
@@ -161,14 +320,21 @@ fn delete_socket(socket_path: &str) {
     }
 }
 
-pub fn spawn_server(socket_path: &str, config: Config) {
-    delete_socket(socket_path);
-
-    let listener = UnixListener::bind(socket_path).unwrap();
-    let (tx, rx): (Sender<String>, Receiver<String>) = std::sync::mpsc::channel();
+// `listener` is bound to `socket_path`, see bind_socket
+pub fn spawn_server(listener: UnixListener, socket_path: &str, config: Config) {
+    let (tx, rx): (Sender<(String, Sender<String>)>, Receiver<_>) = mpsc::channel();
+    // i3bar sends clicks on stdin, waybar runs its own on-click commands instead
+    if config.output == Output::I3bar {
+        let tx = tx.clone();
+        thread::spawn(|| read_clicks(tx));
+    }
+    let watchers = Arc::new(Mutex::new(vec![]));
+    let status = Arc::new(Mutex::new(Status::default()));
     {
         let socket_path = socket_path.to_owned();
-        thread::spawn(|| handle_client(rx, socket_path, config));
+        let watchers = watchers.clone();
+        let status = status.clone();
+        thread::spawn(|| handle_client(rx, socket_path, config, watchers, status));
     }
 
     for stream in listener.incoming() {
@@ -180,13 +346,44 @@ pub fn spawn_server(socket_path: &str, config: Config) {
                     .read_to_string(&mut message)
                     .expect("Failed to read UNIX stream");
 
-                if message.contains("exit") {
+                if message == "exit" {
                     delete_socket(socket_path);
+                    let (reply_tx, reply_rx) = mpsc::channel();
+                    if tx.send((message, reply_tx)).is_ok() {
+                        let _ = reply_rx.recv_timeout(Duration::from_secs(1));
+                    }
                     break;
                 }
-                tx.send(message.to_string()).unwrap();
+
+                // someone checking whether the server is up
+                if message.is_empty() {
+                    continue;
+                }
+
+                // answered here, so shell prompts don't wait on the timer
+                if is_status(&message) {
+                    let reply = status.lock().unwrap().reply(&message);
+                    let _ = stream.write_all(reply.as_bytes());
+                    continue;
+                }
+
+                // watchers keep the stream open, the timer writes to it from now on
+                if message == WATCH_OPERATION {
+                    watchers.lock().unwrap().push((stream, Feed::State));
+                    continue;
+                }
+                if message == BAR_OPERATION {
+                    watchers.lock().unwrap().push((stream, Feed::Bar));
+                    continue;
+                }
+
+                let (reply_tx, reply_rx) = mpsc::channel();
+                tx.send((message, reply_tx)).unwrap();
+                if let Ok(reply) = reply_rx.recv_timeout(Duration::from_secs(1)) {
+                    let _ = stream.write_all(reply.as_bytes());
+                }
             }
-            Err(err) => println!("Error: {}", err),
+            Err(err) => eprintln!("Error: {}", err),
         }
     }
 }
@@ -206,106 +403,489 @@ pub fn get_existing_sockets(binary_name: &str) -> Vec<String> {
     files
 }
 
-pub fn send_message_socket(socket_path: &str, msg: &str) -> Result<(), Error> {
+pub fn is_reachable(socket_path: &str) -> bool {
+    UnixStream::connect(socket_path).is_ok()
+}
+
+// fails with AddrInUse while a running instance has the socket.
+// a socket left behind by an instance that didn't exit cleanly is taken over
+pub fn bind_socket(socket_path: &str) -> Result<UnixListener, Error> {
+    match UnixListener::bind(socket_path) {
+        Err(e) if e.kind() == ErrorKind::AddrInUse && !is_reachable(socket_path) => {
+            delete_socket(socket_path);
+            UnixListener::bind(socket_path)
+        }
+        result => result,
+    }
+}
+
+fn instance_socket(binary_name: &str, nr: usize) -> String {
+    format!("{}/{binary_name}{nr}.socket", env::temp_dir().display())
+}
+
+// only the first instance, numbered 0, notifies, runs hooks and so on.
+// that's the daemon, unless a module in a bar was there before it
+fn instance_number(socket_path: &str, binary_name: &str) -> i32 {
+    match socket_number(socket_path) {
+        Some(nr) => nr as i32,
+        None if is_reachable(&instance_socket(binary_name, 0)) => -1,
+        None => 0,
+    }
+}
+
+// binds the socket for a new instance, with the lowest number no running instance has.
+// binding is what claims it, so instances starting at the same time each get their own
+pub fn free_socket(binary_name: &str) -> Result<(String, UnixListener), Error> {
+    // a running daemon is the first instance already
+    let first = if is_reachable(&daemon_socket(binary_name)) {
+        1
+    } else {
+        0
+    };
+    for nr in first.. {
+        let socket_path = instance_socket(binary_name, nr);
+        match bind_socket(&socket_path) {
+            Ok(listener) => return Ok((socket_path, listener)),
+            Err(e) if e.kind() == ErrorKind::AddrInUse => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!()
+}
+
+// the socket of the daemon, which bars connect to. it has no number since there's only one
+pub fn daemon_socket(binary_name: &str) -> String {
+    format!("{}/{binary_name}.socket", env::temp_dir().display())
+}
+
+// starts a daemon in the background, detached from the terminal, and waits until it's listening.
+// returns its socket
+pub fn spawn_detached(binary_name: &str, config_file: Option<&str>) -> Result<String, Error> {
+    let mut command = Command::new(env::current_exe()?);
+    // the name it was called by decides the name of the socket
+    if let Some(name) = env::args_os().next() {
+        command.arg0(name);
+    }
+    command.arg(DAEMON_OPERATION);
+    if let Some(path) = config_file {
+        command.args(["--config", path]);
+    }
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        // ctrl-c in the terminal it was started from mustn't stop it
+        .process_group(0)
+        .spawn()?;
+
+    let socket_path = daemon_socket(binary_name);
+    for _ in 0..30 {
+        if is_reachable(&socket_path) {
+            return Ok(socket_path);
+        }
+        thread::sleep(SLEEP_DURATION);
+    }
+    Err(Error::new(
+        ErrorKind::TimedOut,
+        "the server didn't start listening",
+    ))
+}
+
+// returns whatever the server replied with, which is empty for most operations
+pub fn send_message_socket(socket_path: &str, msg: &str) -> Result<String, Error> {
     let mut stream = UnixStream::connect(socket_path)?;
+    // don't hang forever on a server that stopped responding
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
     stream.write_all(msg.as_bytes())?;
+    stream.shutdown(Shutdown::Write)?;
+
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply)?;
+    Ok(reply)
+}
+
+// prints every state the server sends until it goes away
+pub fn watch_socket(socket_path: &str, out: &mut impl Write) -> Result<(), Error> {
+    let mut stream = UnixStream::connect(socket_path)?;
+    stream.write_all(WATCH_OPERATION.as_bytes())?;
+    stream.shutdown(Shutdown::Write)?;
+
+    for line in BufReader::new(stream).lines() {
+        writeln!(out, "{}", line?)?;
+        out.flush()?;
+    }
     Ok(())
 }
 
+// the number of the instance behind a socket, e.g. 2 for /tmp/waybar-module-pomodoro2.socket
+fn socket_number(socket_path: &str) -> Option<usize> {
+    let stem = socket_path.strip_suffix(".socket")?;
+    let name = stem.trim_end_matches(|c: char| c.is_ascii_digit());
+    stem[name.len()..].parse().ok()
+}
+
+// puts the sockets in the order of their instances, 2 before 10
+pub fn sort_sockets(sockets: &mut [String]) {
+    sockets.sort_by(|a, b| (socket_number(a), a).cmp(&(socket_number(b), b)));
+}
+
+// sends to every instance, so the timers of all bars stay in step
+// returns the first reply with something in it, e.g. an error
+pub fn broadcast_message(binary_name: &str, msg: &str) -> Result<String, Error> {
+    let mut result: Result<String, Error> =
+        Err(Error::new(ErrorKind::NotFound, "no running instance"));
+    for socket in get_existing_sockets(binary_name) {
+        match send_message_socket(&socket, msg) {
+            Ok(reply) if result.as_ref().map_or(true, |reply| reply.is_empty()) => {
+                result = Ok(reply)
+            }
+            Ok(_) => {}
+            // stale sockets are left behind by instances that didn't exit cleanly
+            Err(e) if result.is_err() => result = Err(e),
+            Err(_) => {}
+        }
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::models::profile::Profile;
+    use crate::utils::consts::MINUTE;
     use crate::LONG_BREAK_TIME;
     use crate::SHORT_BREAK_TIME;
     use fs::File;
     use utils::consts::WORK_TIME;
 
     use super::*;
-    use crate::services::server::CycleType;
 
     fn create_timer() -> Timer {
         Timer::new(WORK_TIME, SHORT_BREAK_TIME, LONG_BREAK_TIME, 0)
     }
 
-    fn get_time(timer: &Timer, cycle: CycleType) -> u16 {
-        match cycle {
-            CycleType::Work => timer.times[0],
-            CycleType::ShortBreak => timer.times[1],
-            CycleType::LongBreak => timer.times[2],
+    fn get_time(timer: &Timer, phase: Phase) -> u64 {
+        match phase {
+            Phase::Work => timer.times[0],
+            Phase::ShortBreak => timer.times[1],
+            Phase::LongBreak => timer.times[2],
         }
     }
 
     #[test]
-    fn test_send_notification_work() {
-        send_notification(CycleType::Work);
+    fn test_process_message_set_work() {
+        let mut timer = create_timer();
+        process_message(
+            &mut timer,
+            &Message::new("set-work", "30").encode(),
+            &mut Config::default(),
+        );
+        assert_eq!(get_time(&timer, Phase::Work), 30 * MINUTE);
     }
 
     #[test]
-    fn test_send_notification_short_break() {
-        send_notification(CycleType::ShortBreak);
+    fn test_process_message_set_short() {
+        let mut timer = create_timer();
+        process_message(
+            &mut timer,
+            &Message::new("set-short", "3").encode(),
+            &mut Config::default(),
+        );
+        assert_eq!(get_time(&timer, Phase::ShortBreak), 3 * MINUTE);
     }
 
     #[test]
-    fn test_send_notification_long_break() {
-        send_notification(CycleType::LongBreak);
+    fn test_process_message_set_long() {
+        let mut timer = create_timer();
+        process_message(
+            &mut timer,
+            &Message::new("set-long", "10").encode(),
+            &mut Config::default(),
+        );
+        assert_eq!(get_time(&timer, Phase::LongBreak), 10 * MINUTE);
     }
 
     #[test]
-    fn test_format_time() {
-        assert_eq!(format_time(300, 600), "05:00");
-        assert_eq!(format_time(59, 60), "00:01");
-        assert_eq!(format_time(0, 120), "02:00");
+    fn test_process_message_set_work_config() {
+        let mut timer = create_timer();
+        let mut config = Config::default();
+        process_message(
+            &mut timer,
+            &Message::new("set-work", "50m").encode(),
+            &mut config,
+        );
+        assert_eq!(config.work_time, 50 * MINUTE);
+
+        let reply = process_message(
+            &mut timer,
+            &Message::new("get", "work_time").encode(),
+            &mut config,
+        );
+        assert_eq!(reply, Some("50m".to_string()));
     }
 
     #[test]
-    fn test_create_message() {
-        let message = "Pomodoro";
-        let tooltip = "Tooltip";
-        let class = vec!["Class".to_owned()];
-
-        let result = create_message(message.to_string(), tooltip, &class);
-        let expected = format!(
-            "{{\"text\": \"{}\", \"tooltip\": \"{}\", \"class\": [\"{}\"], \"alt\": \"{}\"}}",
-            message,
-            tooltip,
-            // FIXME: yeah
-            class.first().unwrap(),
-            ""
+    fn test_process_message_set_seconds() {
+        let mut timer = create_timer();
+        process_message(
+            &mut timer,
+            &Message::new("set-work", "90s").encode(),
+            &mut Config::default(),
         );
-        assert!(result == expected);
+        assert_eq!(get_time(&timer, Phase::Work), 90);
     }
 
     #[test]
-    fn test_process_message_set_work() {
+    fn test_process_message_invalid_duration() {
         let mut timer = create_timer();
-        process_message(&mut timer, &Message::new("set-work", 30).encode());
-        assert_eq!(get_time(&timer, CycleType::Work), 30 * MINUTE);
+        process_message(
+            &mut timer,
+            &Message::new("set-work", "abc").encode(),
+            &mut Config::default(),
+        );
+        assert_eq!(get_time(&timer, Phase::Work), WORK_TIME);
     }
 
     #[test]
-    fn test_process_message_set_short() {
+    fn test_process_message_add() {
         let mut timer = create_timer();
-        process_message(&mut timer, &Message::new("set-short", 3).encode());
-        assert_eq!(get_time(&timer, CycleType::ShortBreak), 3 * MINUTE);
+        process_message(
+            &mut timer,
+            &Message::new("add", "5m").encode(),
+            &mut Config::default(),
+        );
+        assert_eq!(timer.get_current_time(), WORK_TIME + 5 * MINUTE);
+        assert_eq!(get_time(&timer, Phase::Work), WORK_TIME);
     }
 
     #[test]
-    fn test_process_message_set_long() {
+    fn test_process_message_sub() {
+        let mut timer = create_timer();
+        process_message(
+            &mut timer,
+            &Message::new("sub", "5").encode(),
+            &mut Config::default(),
+        );
+        assert_eq!(timer.get_current_time(), WORK_TIME - 5 * MINUTE);
+        assert_eq!(get_time(&timer, Phase::Work), WORK_TIME);
+    }
+
+    #[test]
+    fn test_process_message_profile() {
+        let mut timer = create_timer();
+        let mut config = Config::default();
+        config.profiles.insert(
+            "deep".to_string(),
+            Profile {
+                work_time: 50 * MINUTE,
+                short_break: 10 * MINUTE,
+                long_break: 30 * MINUTE,
+                intervals: 3,
+                auto_work: true,
+                auto_short_break: true,
+                auto_long_break: false,
+            },
+        );
+
+        timer.elapsed_time = 42;
+        process_message(
+            &mut timer,
+            &Message::new("profile", "deep").encode(),
+            &mut config,
+        );
+        assert_eq!(timer.times, [50 * MINUTE, 10 * MINUTE, 30 * MINUTE]);
+        assert_eq!(timer.elapsed_time, 0);
+        assert_eq!(config.intervals, 3);
+        assert!(config.auto_short_break);
+        assert!(!config.auto_long_break);
+        assert_eq!(config.profile, Some("deep".to_string()));
+
+        process_message(
+            &mut timer,
+            &Message::new("profile", "nope").encode(),
+            &mut config,
+        );
+        assert_eq!(timer.times, [50 * MINUTE, 10 * MINUTE, 30 * MINUTE]);
+        assert_eq!(config.profile, Some("deep".to_string()));
+    }
+
+    #[test]
+    fn test_process_message_set_and_get() {
         let mut timer = create_timer();
-        process_message(&mut timer, &Message::new("set-long", 10).encode());
-        assert_eq!(get_time(&timer, CycleType::LongBreak), 10 * MINUTE);
+        let mut config = Config::default();
+
+        timer.elapsed_time = 42;
+        let reply = process_message(
+            &mut timer,
+            &Message::new("set", "work_time 50m").encode(),
+            &mut config,
+        );
+        assert_eq!(reply, None);
+        assert_eq!(config.work_time, 50 * MINUTE);
+        assert_eq!(get_time(&timer, Phase::Work), 50 * MINUTE);
+        assert_eq!(timer.elapsed_time, 0);
+
+        let reply = process_message(
+            &mut timer,
+            &Message::new("set", "format [{profile}] {remaining}").encode(),
+            &mut config,
+        );
+        assert_eq!(reply, None);
+        assert_eq!(config.format, "[{profile}] {remaining}");
+
+        let reply = process_message(
+            &mut timer,
+            &Message::new("get", "work_time").encode(),
+            &mut config,
+        );
+        assert_eq!(reply, Some("50m".to_string()));
+
+        let reply = process_message(
+            &mut timer,
+            &Message::new("set", "intervals many").encode(),
+            &mut config,
+        );
+        assert_eq!(
+            reply,
+            Some("err: intervals must be a number between 1 and 255".to_string())
+        );
+    }
+
+    #[test]
+    fn test_status() {
+        let timer = create_timer();
+        let config = Config {
+            format: "{phase} {remaining}".to_string(),
+            ..Default::default()
+        };
+        let status = Status {
+            format: config.format.clone(),
+            placeholders: placeholders(&timer, &config),
+        };
+
+        assert!(is_status(STATUS_OPERATION));
+        assert_eq!(status.reply(STATUS_OPERATION), "work 25:00");
+
+        let message = Message::new(STATUS_OPERATION, "{remaining}  [{completed}]").encode();
+        assert!(is_status(&message));
+        assert_eq!(status.reply(&message), "25:00 [0]");
+        assert!(!is_status("start"));
+    }
+
+    #[test]
+    fn test_process_message_save() {
+        let filepath = env::temp_dir().join("waybar-module-pomodoro_test_server_save.toml");
+        let _ = fs::remove_file(&filepath);
+        let mut timer = create_timer();
+        let mut config = Config {
+            config_file: Some(filepath.clone()),
+            ..Default::default()
+        };
+
+        process_message(
+            &mut timer,
+            &Message::new("set", "auto_work yes").encode(),
+            &mut config,
+        );
+        let reply = process_message(
+            &mut timer,
+            &Message::new("save", "auto_work").encode(),
+            &mut config,
+        );
+        assert_eq!(reply, None);
+        assert_eq!(fs::read_to_string(&filepath).unwrap(), "auto_work = true\n");
+
+        let mut config = Config::default();
+        let reply = process_message(
+            &mut timer,
+            &Message::new("save", "auto_work").encode(),
+            &mut config,
+        );
+        assert_eq!(reply, Some("err: no config file to save to".to_string()));
+
+        fs::remove_file(filepath).unwrap();
+    }
+
+    #[test]
+    fn test_process_message_invalid_time() {
+        let mut timer = create_timer();
+        let mut config = Config::default();
+
+        for value in ["0", "200h", "soon"] {
+            let reply = process_message(
+                &mut timer,
+                &Message::new("set-work", value).encode(),
+                &mut config,
+            );
+            assert!(reply.is_some_and(|reply| reply.starts_with("err: invalid value for set-work")));
+        }
+        assert_eq!(get_time(&timer, Phase::Work), WORK_TIME);
     }
 
     #[test]
     fn test_process_message_start() {
         let mut timer = create_timer();
-        process_message(&mut timer, "start");
+        process_message(&mut timer, "start", &mut Config::default());
         assert!(timer.running);
     }
 
     #[test]
     fn test_process_message_stop() {
         let mut timer = create_timer();
-        process_message(&mut timer, "stop");
+        process_message(&mut timer, "stop", &mut Config::default());
+        assert!(!timer.running);
+    }
+
+    #[test]
+    fn test_process_message_skip() {
+        let mut timer = create_timer();
+        timer.iterations = 2;
+        process_message(&mut timer, "skip", &mut Config::default());
+        assert!(timer.is_break());
+        assert_eq!(timer.iterations, 2);
+    }
+
+    #[test]
+    fn test_process_message_acknowledge_overtime() {
+        let mut timer = create_timer();
+        let mut config = Config {
+            overtime: true,
+            ..Default::default()
+        };
+        timer.start();
+        timer.elapsed_time = WORK_TIME + MINUTE;
+        timer.update_state(&config);
+        assert!(timer.in_overtime);
+
+        process_message(&mut timer, "toggle", &mut config);
+        assert!(timer.is_break());
+        assert!(timer.running);
+
+        timer.elapsed_time = SHORT_BREAK_TIME;
+        timer.update_state(&config);
+        process_message(&mut timer, "stop", &mut config);
+        assert!(!timer.is_break());
+        assert!(!timer.running);
+    }
+
+    #[test]
+    fn test_process_message_stop_flowtime() {
+        let mut timer = create_timer();
+        let mut config = Config {
+            flowtime: true,
+            flowtime_ratio: 0.5,
+            ..Default::default()
+        };
+        timer.start();
+        timer.elapsed_time = 20 * MINUTE;
+
+        process_message(&mut timer, "stop", &mut config);
+        assert!(timer.is_break());
+        assert!(timer.running);
+        assert_eq!(timer.get_current_time(), 10 * MINUTE);
+
+        // a break is stopped as usual
+        process_message(&mut timer, "stop", &mut config);
+        assert!(timer.is_break());
         assert!(!timer.running);
     }
 
@@ -347,4 +927,135 @@ mod tests {
 
         std::fs::remove_file(socket_path).unwrap();
     }
+
+    #[test]
+    fn test_watch_socket() {
+        let socket_path = env::temp_dir()
+            .join("waybar-module-pomodoro_test_watch.socket")
+            .to_string_lossy()
+            .to_string();
+        let _ = std::fs::remove_file(&socket_path);
+        let listener = UnixListener::bind(&socket_path).unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut message = String::new();
+            stream.read_to_string(&mut message).unwrap();
+            stream
+                .write_all(b"{\"running\":false}\n{\"running\":true}\n")
+                .unwrap();
+            message
+        });
+
+        // returns once the server closes the stream
+        let mut out = vec![];
+        watch_socket(&socket_path, &mut out).unwrap();
+        assert_eq!(server.join().unwrap(), WATCH_OPERATION);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"running\":false}\n{\"running\":true}\n"
+        );
+
+        let _ = std::fs::remove_file(socket_path);
+    }
+
+    #[test]
+    fn test_free_socket() {
+        let binary_name = "waybar-module-pomodoro_test_free";
+        let path = |nr: usize| instance_socket(binary_name, nr);
+        let _ = fs::remove_file(path(0));
+        let _listener = UnixListener::bind(path(0)).unwrap();
+        // left behind by an instance that crashed, so it doesn't count
+        File::create(path(1)).unwrap();
+
+        let (socket_path, _second) = free_socket(binary_name).unwrap();
+        assert_eq!(socket_path, path(1));
+        // taken as soon as it's returned
+        assert!(is_reachable(&path(1)));
+        assert_eq!(free_socket(binary_name).unwrap().0, path(2));
+
+        for nr in 0..3 {
+            let _ = fs::remove_file(path(nr));
+        }
+        assert_eq!(free_socket(binary_name).unwrap().0, path(0));
+        let _ = fs::remove_file(path(0));
+
+        // next to a daemon, which is the first instance
+        let daemon = daemon_socket(binary_name);
+        let _ = fs::remove_file(&daemon);
+        let _daemon = UnixListener::bind(&daemon).unwrap();
+        assert_eq!(free_socket(binary_name).unwrap().0, path(1));
+        assert_eq!(instance_number(&daemon, binary_name), 0);
+        let _ = fs::remove_file(path(1));
+
+        // unless a module was there before it
+        let _first = UnixListener::bind(path(0)).unwrap();
+        assert_eq!(instance_number(&daemon, binary_name), -1);
+        assert_eq!(instance_number(&path(0), binary_name), 0);
+        let _ = fs::remove_file(path(0));
+        let _ = fs::remove_file(daemon);
+    }
+
+    #[test]
+    fn test_sort_sockets() {
+        let mut sockets = vec![
+            "/tmp/waybar-module-pomodoro10.socket".to_string(),
+            "/tmp/waybar-module-pomodoro2.socket".to_string(),
+            "/tmp/waybar-module-pomodoro0.socket".to_string(),
+        ];
+        sort_sockets(&mut sockets);
+        assert_eq!(
+            sockets,
+            [
+                "/tmp/waybar-module-pomodoro0.socket",
+                "/tmp/waybar-module-pomodoro2.socket",
+                "/tmp/waybar-module-pomodoro10.socket",
+            ]
+        );
+        assert_eq!(
+            socket_number(&daemon_socket("waybar-module-pomodoro")),
+            None
+        );
+
+        // the daemon comes first
+        sockets.push(daemon_socket("waybar-module-pomodoro"));
+        sort_sockets(&mut sockets);
+        assert_eq!(sockets[0], daemon_socket("waybar-module-pomodoro"));
+    }
+
+    #[test]
+    fn test_broadcast_message() {
+        let binary_name = "waybar-module-pomodoro_test_broadcast";
+        let path = |nr: usize| {
+            env::temp_dir()
+                .join(format!("{binary_name}{nr}.socket"))
+                .to_string_lossy()
+                .to_string()
+        };
+        let (tx, messages) = mpsc::channel();
+        for (nr, reply) in [(0, ""), (1, "err: nope")] {
+            let _ = fs::remove_file(path(nr));
+            let listener = UnixListener::bind(path(nr)).unwrap();
+            let tx = tx.clone();
+            thread::spawn(move || {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut message = String::new();
+                stream.read_to_string(&mut message).unwrap();
+                stream.write_all(reply.as_bytes()).unwrap();
+                tx.send((nr, message)).unwrap();
+            });
+        }
+        // left behind by an instance that crashed
+        File::create(path(2)).unwrap();
+
+        let reply = broadcast_message(binary_name, "skip").unwrap();
+        assert_eq!(reply, "err: nope");
+        let mut received: Vec<_> = messages.iter().take(2).collect();
+        received.sort();
+        assert_eq!(received, [(0, "skip".to_string()), (1, "skip".to_string())]);
+
+        for nr in 0..3 {
+            let _ = fs::remove_file(path(nr));
+        }
+        assert!(broadcast_message(binary_name, "skip").is_err());
+    }
 }
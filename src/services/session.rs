@@ -0,0 +1,175 @@
+use std::{collections::HashMap, env};
+
+use zbus::{
+    blocking::{Connection, MessageIterator},
+    zvariant::{OwnedObjectPath, OwnedValue},
+    MatchRule, MessageType,
+};
+
+use super::server::broadcast_message;
+
+const LOGIN_BUS: &str = "org.freedesktop.login1";
+const LOGIN_PATH: &str = "/org/freedesktop/login1";
+const MANAGER_INTERFACE: &str = "org.freedesktop.login1.Manager";
+
+// the session the module runs in, or the one it was started from
+fn session_path(connection: &Connection) -> zbus::Result<OwnedObjectPath> {
+    let id = env::var("XDG_SESSION_ID").unwrap_or("auto".to_string());
+    connection
+        .call_method(
+            Some(LOGIN_BUS),
+            LOGIN_PATH,
+            Some(MANAGER_INTERFACE),
+            "GetSession",
+            &(id,),
+        )?
+        .body()
+        .deserialize()
+}
+
+#[derive(Default)]
+struct Presence {
+    locked: bool,
+    idle: bool,
+}
+
+impl Presence {
+    fn away(&self) -> bool {
+        self.locked || self.idle
+    }
+}
+
+// follows the session on logind, telling every instance when it's locked or idle and when it's back
+pub fn watch(connection: &Connection, binary_name: &str) -> zbus::Result<()> {
+    let path = session_path(connection)?;
+    let rule = MatchRule::builder()
+        .msg_type(MessageType::Signal)
+        .path(path)?
+        .build();
+
+    let mut presence = Presence::default();
+    for message in MessageIterator::for_match_rule(rule, connection, None)? {
+        let message = message?;
+        let was_away = presence.away();
+        match message.header().member().map(|member| member.as_str()) {
+            Some("Lock") => presence.locked = true,
+            Some("Unlock") => presence.locked = false,
+            Some("PropertiesChanged") => {
+                let (_, changed, _): (String, HashMap<String, OwnedValue>, Vec<String>) =
+                    message.body().deserialize()?;
+                if let Some(idle) = changed.get("IdleHint") {
+                    presence.idle = idle.downcast_ref()?;
+                }
+            }
+            _ => continue,
+        }
+
+        if presence.away() != was_away {
+            let message = if presence.away() { "away" } else { "back" };
+            if let Err(e) = broadcast_message(binary_name, message) {
+                eprintln!("err: unable to send {message} to the servers, err == {e}");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Read, os::unix::net::UnixListener, sync::mpsc, thread, time::Duration};
+
+    use zbus::{interface, SignalContext};
+
+    use super::*;
+    use crate::services::dbus::testing::PrivateBus;
+
+    const SESSION_PATH: &str = "/org/freedesktop/login1/session/_1";
+
+    struct FakeManager;
+
+    #[interface(name = "org.freedesktop.login1.Manager")]
+    impl FakeManager {
+        fn get_session(&self, _id: &str) -> OwnedObjectPath {
+            OwnedObjectPath::try_from(SESSION_PATH).unwrap()
+        }
+    }
+
+    #[derive(Default)]
+    struct FakeSession {
+        idle: bool,
+    }
+
+    #[interface(name = "org.freedesktop.login1.Session")]
+    impl FakeSession {
+        #[zbus(property)]
+        fn idle_hint(&self) -> bool {
+            self.idle
+        }
+
+        #[zbus(signal)]
+        async fn lock(ctxt: &SignalContext<'_>) -> zbus::Result<()>;
+
+        #[zbus(signal)]
+        async fn unlock(ctxt: &SignalContext<'_>) -> zbus::Result<()>;
+    }
+
+    #[test]
+    fn test_watch() {
+        let Some(bus) = PrivateBus::start() else {
+            println!("dbus-daemon not available, skipping");
+            return;
+        };
+        let logind = bus
+            .builder()
+            .name(LOGIN_BUS)
+            .unwrap()
+            .serve_at(LOGIN_PATH, FakeManager)
+            .unwrap()
+            .serve_at(SESSION_PATH, FakeSession::default())
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let binary_name = "waybar-module-pomodoro_test_session";
+        let socket_path = std::env::temp_dir()
+            .join(format!("{binary_name}0.socket"))
+            .to_string_lossy()
+            .to_string();
+        let _ = std::fs::remove_file(&socket_path);
+        let listener = UnixListener::bind(&socket_path).unwrap();
+        let (tx, messages) = mpsc::channel();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut message = String::new();
+                let _ = stream.read_to_string(&mut message);
+                let _ = tx.send(message);
+            }
+        });
+
+        let connection = bus.builder().build().unwrap();
+        thread::spawn(move || watch(&connection, binary_name));
+        // give the watcher time to subscribe
+        thread::sleep(Duration::from_millis(200));
+
+        let iface = logind
+            .object_server()
+            .interface::<_, FakeSession>(SESSION_PATH)
+            .unwrap();
+        let ctxt = iface.signal_context();
+        let timeout = Duration::from_secs(5);
+
+        zbus::block_on(FakeSession::lock(ctxt)).unwrap();
+        assert_eq!(messages.recv_timeout(timeout).unwrap(), "away");
+
+        // still away while idle, until both are over
+        iface.get_mut().idle = true;
+        zbus::block_on(iface.get().idle_hint_changed(ctxt)).unwrap();
+        zbus::block_on(FakeSession::unlock(ctxt)).unwrap();
+        iface.get_mut().idle = false;
+        zbus::block_on(iface.get().idle_hint_changed(ctxt)).unwrap();
+        assert_eq!(messages.recv_timeout(timeout).unwrap(), "back");
+        assert!(messages.recv_timeout(Duration::from_millis(200)).is_err());
+
+        let _ = std::fs::remove_file(socket_path);
+    }
+}
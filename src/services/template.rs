@@ -0,0 +1,163 @@
+use crate::{
+    models::{config::Config, sequence::default_name},
+    utils::consts::{HOUR, MINUTE},
+};
+
+use super::timer::Timer;
+
+pub fn format_time(elapsed_time: u64, max_time: u64) -> String {
+    let time = max_time.saturating_sub(elapsed_time);
+
+    let hour = time / HOUR;
+    let minute = (time % HOUR) / MINUTE;
+    let second = time % MINUTE;
+
+    if hour > 0 {
+        return format!("{:02}:{:02}:{:02}", hour, minute, second);
+    }
+
+    format!("{:02}:{:02}", minute, second)
+}
+
+// values that can be used in format strings, e.g. "{state} {remaining} {icon}"
+pub fn placeholders(state: &Timer, config: &Config) -> Vec<(&'static str, String)> {
+    let phase = match state.current_step() {
+        Some(step) => step.name.clone(),
+        None => default_name(state.phase).to_string(),
+    };
+
+    // flowtime work shows how long it's been going instead, and overtime how far past it is
+    // an automatically started phase can also be waiting for its countdown
+    let remaining = if state.countdown > 0 {
+        format!("starting in {}s", state.countdown)
+    } else if state.counts_up(config) {
+        format_time(0, state.elapsed_time)
+    } else if state.in_overtime {
+        format!("+{}", format_time(0, state.get_overtime()))
+    } else {
+        format_time(state.elapsed_time, state.get_current_time())
+    };
+
+    vec![
+        (
+            "state",
+            config.get_play_pause_icon(state.running).to_string(),
+        ),
+        ("remaining", remaining),
+        (
+            "icon",
+            config
+                .get_cycle_icon(
+                    state.is_break(),
+                    state.current_step().and_then(|step| step.icon.as_deref()),
+                )
+                .to_string(),
+        ),
+        ("phase", phase),
+        ("profile", config.profile.clone().unwrap_or_default()),
+        ("completed", state.session_completed.to_string()),
+    ]
+}
+
+pub fn render(template: &str, placeholders: &[(&str, String)]) -> String {
+    let mut result = template.to_string();
+    for (key, value) in placeholders {
+        result = result.replace(&format!("{{{key}}}"), value);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::phase::Phase,
+        utils::{
+            consts::{LONG_BREAK_TIME, SHORT_BREAK_TIME, WORK_TIME},
+            helper::trim_whitespace,
+        },
+        BREAK_ICON, PLAY_ICON, WORK_ICON,
+    };
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(300, 600), "05:00");
+        assert_eq!(format_time(59, 60), "00:01");
+        assert_eq!(format_time(0, 120), "02:00");
+        assert_eq!(format_time(0, 20 * HOUR), "20:00:00");
+        assert_eq!(format_time(0, 150 * HOUR), "150:00:00");
+        assert_eq!(format_time(120, 60), "00:00");
+    }
+
+    #[test]
+    fn test_render_default_format() {
+        let config = Config::default();
+        let state = Timer::new(WORK_TIME, SHORT_BREAK_TIME, LONG_BREAK_TIME, 0);
+
+        let text = render(&config.format, &placeholders(&state, &config));
+        assert_eq!(
+            trim_whitespace(&text),
+            format!("{} 25:00 {}", PLAY_ICON, WORK_ICON)
+        );
+    }
+
+    #[test]
+    fn test_render_placeholders() {
+        let config = Config {
+            profile: Some("deep".to_string()),
+            ..Default::default()
+        };
+        let mut state = Timer::new(WORK_TIME, SHORT_BREAK_TIME, LONG_BREAK_TIME, 0);
+        state.phase = Phase::LongBreak;
+        state.session_completed = 3;
+
+        let text = render(
+            "[{profile}] {phase} {icon} {remaining} ({completed}) {unknown}",
+            &placeholders(&state, &config),
+        );
+        assert_eq!(
+            text,
+            format!("[deep] long break {} 15:00 (3) {{unknown}}", BREAK_ICON)
+        );
+    }
+
+    #[test]
+    fn test_render_flowtime() {
+        let config = Config {
+            flowtime: true,
+            ..Default::default()
+        };
+        let mut state = Timer::new(WORK_TIME, SHORT_BREAK_TIME, LONG_BREAK_TIME, 0);
+        state.elapsed_time = 42 * 60 + 5;
+
+        assert_eq!(
+            render("{remaining}", &placeholders(&state, &config)),
+            "42:05"
+        );
+    }
+
+    #[test]
+    fn test_render_countdown() {
+        let config = Config::default();
+        let mut state = Timer::new(WORK_TIME, SHORT_BREAK_TIME, LONG_BREAK_TIME, 0);
+        state.countdown = 10;
+
+        assert_eq!(
+            render("{remaining}", &placeholders(&state, &config)),
+            "starting in 10s"
+        );
+    }
+
+    #[test]
+    fn test_render_overtime() {
+        let config = Config::default();
+        let mut state = Timer::new(WORK_TIME, SHORT_BREAK_TIME, LONG_BREAK_TIME, 0);
+        state.elapsed_time = WORK_TIME + 3 * 60 + 12;
+        state.in_overtime = true;
+
+        assert_eq!(
+            render("{remaining}", &placeholders(&state, &config)),
+            "+03:12"
+        );
+    }
+}
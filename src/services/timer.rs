@@ -1,62 +1,201 @@
-use serde::{Deserialize, Serialize};
+use std::time::Instant;
 
-use crate::{models::config::Config, utils::consts::SLEEP_TIME};
+use serde::{Deserialize, Serialize};
 
-use super::server::send_notification;
+use crate::{
+    models::{
+        config::Config,
+        phase::{deserialize_cached, Phase},
+        sequence::Step,
+    },
+    utils::consts::{MAX_TIME, SLEEP_TIME},
+};
 
-pub enum CycleType {
-    Work,
-    ShortBreak,
-    LongBreak,
-}
+use super::events::Event;
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct Timer {
-    pub current_index: usize,
-    pub elapsed_millis: u16,
-    pub elapsed_time: u16,
-    pub times: [u16; 3],
+    #[serde(alias = "current_index", deserialize_with = "deserialize_cached")]
+    pub phase: Phase,
+    pub elapsed_millis: u64,
+    pub elapsed_time: u64,
+    pub times: [u64; 3],
+    #[serde(default)]
+    pub extra_time: i64,
+    // length of a break earned in flowtime mode, used instead of the configured time
+    #[serde(default)]
+    pub earned_break: Option<u64>,
+    // the phase ran out and is counting past its time, see Config::overtime
+    #[serde(default)]
+    pub in_overtime: bool,
+    // seconds left before an automatically started phase begins, see Config::auto_start_delay
+    #[serde(default)]
+    pub countdown: u64,
+    // the phase is about to run out, see Config::warning
+    #[serde(default)]
+    pub in_warning: bool,
     pub iterations: u8,
-    pub session_completed: u8,
+    pub session_completed: u32,
     pub running: bool,
     pub socket_nr: i32,
+    // custom steps from the config, the usual work/short/long cycle is used when empty
+    #[serde(skip)]
+    pub sequence: Vec<Step>,
+    #[serde(default)]
+    pub step: usize,
+    // events since the last call to take_events, handed to subscribers by the server
+    #[serde(skip)]
+    pub events: Vec<Event>,
+    // when the session was locked or went idle, see Config::pause_on_lock
+    #[serde(skip)]
+    pub away_since: Option<Instant>,
+    // whether the timer was running at the time
+    #[serde(skip)]
+    pub running_away: bool,
 }
 
 impl Timer {
-    pub fn new(work_time: u16, short_break: u16, long_break: u16, socker_nr: i32) -> Timer {
+    pub fn new(work_time: u64, short_break: u64, long_break: u64, socker_nr: i32) -> Timer {
         Timer {
-            current_index: 0,
+            phase: Phase::Work,
             elapsed_millis: 0,
             elapsed_time: 0,
             times: [work_time, short_break, long_break],
+            extra_time: 0,
+            earned_break: None,
+            in_overtime: false,
+            countdown: 0,
+            in_warning: false,
             iterations: 0,
             session_completed: 0,
             running: false,
             socket_nr: socker_nr,
+            sequence: vec![],
+            step: 0,
+            events: vec![],
+            away_since: None,
+            running_away: false,
         }
     }
 
     pub fn reset(&mut self) {
-        self.current_index = 0;
+        self.rewind();
+        self.events.push(Event::Reset);
+    }
+
+    fn rewind(&mut self) {
+        self.step = 0;
+        self.phase = self.sequence.first().map_or(Phase::Work, |step| step.phase);
         self.elapsed_time = 0;
         self.elapsed_millis = 0;
+        self.extra_time = 0;
+        self.earned_break = None;
+        self.in_overtime = false;
+        self.countdown = 0;
+        self.in_warning = false;
         self.iterations = 0;
         self.running = false;
     }
 
+    pub fn set_sequence(&mut self, sequence: Vec<Step>) {
+        self.sequence = sequence;
+        self.rewind();
+    }
+
+    pub fn current_step(&self) -> Option<&Step> {
+        self.sequence.get(self.step)
+    }
+
+    pub fn start(&mut self) {
+        if !self.running {
+            self.running = true;
+            self.events.push(Event::Resumed);
+        }
+    }
+
+    pub fn stop(&mut self) {
+        if self.running {
+            self.running = false;
+            self.events.push(Event::Paused);
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        if self.running {
+            self.stop();
+        } else {
+            self.start();
+        }
+    }
+
+    // the session was locked or went idle
+    pub fn away(&mut self, config: &Config) {
+        if self.away_since.is_some() {
+            return;
+        }
+        self.away_since = Some(Instant::now());
+        self.running_away = self.running;
+        if config.pause_on_lock {
+            self.stop();
+        }
+    }
+
+    // the session is back, restarting work that was left for too long
+    pub fn back(&mut self, config: &Config) {
+        let Some(since) = self.away_since.take() else {
+            return;
+        };
+        if !std::mem::take(&mut self.running_away) {
+            return;
+        }
+
+        let idle_reset = config.idle_reset;
+        if idle_reset > 0 && since.elapsed().as_secs() >= idle_reset && self.phase == Phase::Work {
+            self.restart_phase();
+        }
+        if config.pause_on_lock && config.resume_on_unlock {
+            self.start();
+        }
+    }
+
+    // starts the current phase over, keeping the iterations done so far
+    fn restart_phase(&mut self) {
+        self.elapsed_time = 0;
+        self.elapsed_millis = 0;
+        self.extra_time = 0;
+        self.in_overtime = false;
+        self.in_warning = false;
+    }
+
     pub fn is_break(&self) -> bool {
-        self.current_index != 0
+        self.phase.is_break()
     }
 
-    pub fn set_time(&mut self, cycle: CycleType, input: u16) {
+    pub fn set_time(&mut self, phase: Phase, seconds: u64) {
         self.reset();
 
-        match cycle {
-            CycleType::Work => self.times[0] = input * 60,
-            CycleType::ShortBreak => self.times[1] = input * 60,
-            CycleType::LongBreak => self.times[2] = input * 60,
-        }
-        println!("{:?}", self.times);
+        self.times[phase as usize] = seconds;
+        self.sequence
+            .iter_mut()
+            .filter(|step| step.phase == phase)
+            .for_each(|step| step.duration = seconds);
+    }
+
+    pub fn set_times(&mut self, times: [u64; 3]) {
+        self.reset();
+        self.times = times;
+    }
+
+    // adjusts the remaining time of the current phase only, the configured times are left as is
+    pub fn add_time(&mut self, seconds: u64) {
+        let headroom = MAX_TIME.saturating_sub(self.get_current_time());
+        self.extra_time += seconds.min(headroom) as i64;
+        // adding time to a phase in overtime gives it something to count down again
+        self.in_overtime &= self.get_remaining_time() == 0;
+    }
+
+    pub fn sub_time(&mut self, seconds: u64) {
+        self.extra_time -= seconds.min(self.get_remaining_time()) as i64;
     }
 
     pub fn get_class(&self) -> Vec<String> {
@@ -65,7 +204,9 @@ impl Timer {
         if self.elapsed_millis == 0
             && self.elapsed_time == 0
             && self.iterations == 0
+            && self.step == 0
             && self.session_completed == 0
+            && self.countdown == 0
         {
             return result;
         }
@@ -80,60 +221,197 @@ impl Timer {
             result.push("break".to_owned());
         }
 
+        if self.in_overtime {
+            result.push("overtime".to_owned());
+        }
+
+        if self.countdown > 0 {
+            result.push("starting".to_owned());
+        }
+
+        if self.in_warning {
+            result.push("warning".to_owned());
+        }
+
+        if let Some(class) = self.current_step().and_then(|step| step.class.clone()) {
+            result.push(class);
+        }
+
         result
     }
 
+    // work phases in flowtime mode have no end, they count up until stopped
+    pub fn counts_up(&self, config: &Config) -> bool {
+        config.flowtime && !self.is_break()
+    }
+
     pub fn update_state(&mut self, config: &Config) {
-        if (self.times[self.current_index] - self.elapsed_time) == 0 {
-            // if we're on the last interval and first work, then we want a long break
-            if self.current_index == 0 && self.iterations == config.intervals - 1 {
-                self.current_index = self.times.len() - 1;
-                self.iterations = config.intervals;
+        self.update_warning(config);
+        if self.counts_up(config) || self.get_remaining_time() > 0 {
+            return;
+        }
+
+        // without anything to start automatically, wait for the user in overtime
+        if config.overtime && !self.next_starts_automatically(config) {
+            if !self.in_overtime {
+                self.in_overtime = true;
+                self.events
+                    .push(Event::OvertimeStarted { phase: self.phase });
             }
-            // if we've had our long break, reset everything and start over
-            else if self.current_index == self.times.len() - 1
-                && self.iterations == config.intervals
-            {
-                self.current_index = 0;
-                self.iterations = 0;
-                // since we've gone through a long break, we've also completed a single pomodoro!
-                self.session_completed += 1;
+            return;
+        }
+
+        self.next_phase(config, false);
+    }
+
+    // phases shorter than the warning don't get one, they'd be warned about from the start
+    fn update_warning(&mut self, config: &Config) {
+        let warning = config.warning(self.phase);
+        let remaining = self.get_remaining_time();
+        let in_warning = warning > 0
+            && !self.counts_up(config)
+            && self.get_current_time() > warning
+            && remaining > 0
+            && remaining <= warning;
+
+        if in_warning && !self.in_warning {
+            self.events.push(Event::WarningStarted {
+                phase: self.phase,
+                remaining,
+            });
+        }
+        self.in_warning = in_warning;
+    }
+
+    // moves on from overtime, optionally starting the next phase right away
+    pub fn acknowledge(&mut self, config: &Config, start: bool) {
+        self.next_phase(config, false);
+        if start {
+            self.start();
+        }
+    }
+
+    // the phase that follows the current one once it's done
+    pub fn upcoming_phase(&self, config: &Config) -> Phase {
+        match self.sequence.is_empty() {
+            true => self.phase.next(self.iterations, config.intervals).phase,
+            false => self.sequence[(self.step + 1) % self.sequence.len()].phase,
+        }
+    }
+
+    fn next_starts_automatically(&self, config: &Config) -> bool {
+        config.auto_start(self.upcoming_phase(config))
+    }
+
+    // ends the current phase early, moving on as if it had run out
+    pub fn skip(&mut self, config: &Config) {
+        self.next_phase(config, true);
+    }
+
+    // ends a work phase and starts a break, in flowtime mode this is how work is finished
+    pub fn take_break(&mut self, config: &Config) {
+        if !self.is_break() {
+            self.next_phase(config, !config.flowtime);
+        }
+    }
+
+    fn next_phase(&mut self, config: &Config, skipped: bool) {
+        let phase = self.phase;
+        let elapsed = self.elapsed_time;
+        let planned = if self.counts_up(config) {
+            elapsed
+        } else {
+            self.get_current_time()
+        };
+        let overtime = self.get_overtime();
+        self.events.push(if skipped {
+            Event::Skipped {
+                phase,
+                elapsed,
+                planned,
             }
-            // otherwise, run as normal
-            else {
-                self.current_index = (self.current_index + 1) % 2;
-                if self.current_index == 0 {
-                    self.iterations += 1;
-                }
+        } else {
+            Event::PhaseCompleted {
+                phase,
+                elapsed,
+                planned,
             }
+        });
 
-            self.elapsed_time = 0;
+        if self.sequence.is_empty() {
+            let transition = self.phase.next(self.iterations, config.intervals);
+            self.phase = transition.phase;
+            self.iterations = transition.iterations;
+            if transition.completed {
+                self.session_completed += 1;
+            }
+        } else {
+            // custom sequences simply loop, going through all of the steps counts as a pomodoro
+            self.step = (self.step + 1) % self.sequence.len();
+            self.phase = self.sequence[self.step].phase;
+            if self.step == 0 {
+                self.session_completed += 1;
+            }
+        }
 
-            // if the user has passed either auto flag, we want to keep ticking the timer
-            // NOTE: the is_break() seems to be flipped..?
-            self.running = (config.autob && self.is_break()) || (config.autow && !self.is_break());
+        self.elapsed_time = 0;
+        self.elapsed_millis = 0;
+        self.extra_time = 0;
+        self.earned_break = None;
+        self.in_overtime = false;
+        self.in_warning = false;
+
+        // if the user has asked for this phase to start automatically, we want to keep ticking the timer
+        self.running = config.auto_start(self.phase);
+        self.countdown = match self.running {
+            true => config.auto_start_delay,
+            false => 0,
+        };
+
+        // the break is a fraction of the work that came before it, and starts right away
+        if config.flowtime && phase == Phase::Work && self.is_break() {
+            let earned = (elapsed as f64 * config.flowtime_ratio).round() as u64;
+            self.earned_break = Some(earned.min(MAX_TIME));
+            self.running = true;
+        }
 
-            // only send a notification for the first instance of the module
-            if self.socket_nr == 0 {
-                send_notification(match self.current_index {
-                    0 => CycleType::Work,
-                    1 => CycleType::ShortBreak,
-                    2 => CycleType::LongBreak,
-                    _ => panic!("Invalid cycle type"),
-                });
-            }
+        if config.overtime_break && phase == Phase::Work && self.is_break() {
+            self.add_time(overtime);
         }
+        self.events.push(Event::PhaseStarted { phase: self.phase });
+    }
+
+    pub fn take_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.events)
     }
 
-    pub fn get_current_time(&self) -> u16 {
-        self.times[self.current_index]
+    pub fn get_current_time(&self) -> u64 {
+        let time = match (self.earned_break, self.current_step()) {
+            (Some(time), _) => time,
+            (None, Some(step)) => step.duration,
+            (None, None) => self.times[self.phase as usize],
+        };
+        (time as i64 + self.extra_time).max(0) as u64
+    }
+
+    pub fn get_overtime(&self) -> u64 {
+        self.elapsed_time.saturating_sub(self.get_current_time())
+    }
+
+    pub fn get_remaining_time(&self) -> u64 {
+        self.get_current_time().saturating_sub(self.elapsed_time)
     }
 
     pub fn increment_time(&mut self) {
         self.elapsed_millis += SLEEP_TIME;
         if self.elapsed_millis >= 1000 {
             self.elapsed_millis = 0;
-            self.elapsed_time += 1;
+            // the phase itself only starts once the countdown is over
+            if self.countdown > 0 {
+                self.countdown -= 1;
+            } else {
+                self.elapsed_time += 1;
+            }
         }
     }
 }
@@ -141,7 +419,7 @@ impl Timer {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::utils::consts::SLEEP_DURATION;
+    use crate::utils::consts::{HOUR, SLEEP_DURATION};
     use crate::LONG_BREAK_TIME;
     use crate::SHORT_BREAK_TIME;
     use crate::WORK_TIME;
@@ -154,7 +432,7 @@ mod tests {
     fn test_new_timer() {
         let timer = create_timer();
 
-        assert_eq!(timer.current_index, 0);
+        assert_eq!(timer.phase, Phase::Work);
         assert_eq!(timer.elapsed_millis, 0);
         assert_eq!(timer.elapsed_time, 0);
         assert_eq!(timer.times, [WORK_TIME, SHORT_BREAK_TIME, LONG_BREAK_TIME]);
@@ -166,7 +444,7 @@ mod tests {
     #[test]
     fn test_reset_timer() {
         let mut timer = create_timer();
-        timer.current_index = 2;
+        timer.phase = Phase::LongBreak;
         timer.elapsed_millis = 999;
         timer.elapsed_time = WORK_TIME - 1;
         timer.iterations = 4;
@@ -175,7 +453,7 @@ mod tests {
 
         timer.reset();
 
-        assert_eq!(timer.current_index, 0);
+        assert_eq!(timer.phase, Phase::Work);
         assert_eq!(timer.elapsed_millis, 0);
         assert_eq!(timer.elapsed_time, 0);
         assert_eq!(timer.iterations, 0);
@@ -188,7 +466,7 @@ mod tests {
 
         assert!(!timer.is_break());
 
-        timer.current_index = 1;
+        timer.phase = Phase::ShortBreak;
         assert!(timer.is_break());
     }
 
@@ -196,14 +474,50 @@ mod tests {
     fn test_set_time() {
         let mut timer = create_timer();
 
-        timer.set_time(CycleType::Work, 30);
+        timer.set_time(Phase::Work, 30 * 60);
         assert_eq!(timer.times[0], 30 * 60);
 
-        timer.set_time(CycleType::ShortBreak, 10);
+        timer.set_time(Phase::ShortBreak, 10 * 60);
         assert_eq!(timer.times[1], 10 * 60);
 
-        timer.set_time(CycleType::LongBreak, 20);
-        assert_eq!(timer.times[2], 20 * 60);
+        timer.set_time(Phase::LongBreak, 90);
+        assert_eq!(timer.times[2], 90);
+    }
+
+    #[test]
+    fn test_add_time() {
+        let mut timer = create_timer();
+
+        timer.add_time(5 * 60);
+        assert_eq!(timer.get_current_time(), WORK_TIME + 5 * 60);
+        assert_eq!(timer.times[0], WORK_TIME);
+
+        // the adjustment only lasts for the current cycle
+        timer.skip(&Config::default());
+        assert_eq!(timer.get_current_time(), SHORT_BREAK_TIME);
+
+        timer.add_time(u64::MAX);
+        assert_eq!(timer.get_current_time(), MAX_TIME);
+    }
+
+    #[test]
+    fn test_sub_time() {
+        let mut timer = create_timer();
+        let config = Config::default();
+
+        timer.sub_time(5 * 60);
+        assert_eq!(timer.get_current_time(), WORK_TIME - 5 * 60);
+        assert_eq!(timer.times[0], WORK_TIME);
+
+        // removing more than what's left should end the cycle on the next update
+        timer.elapsed_time = 60;
+        timer.sub_time(WORK_TIME);
+        assert_eq!(timer.get_current_time(), 60);
+
+        timer.update_state(&config);
+        assert_eq!(timer.phase, Phase::ShortBreak);
+        assert_eq!(timer.get_current_time(), SHORT_BREAK_TIME);
+        assert_eq!(timer.iterations, 0);
     }
 
     #[test]
@@ -216,7 +530,7 @@ mod tests {
         timer.elapsed_millis = 1;
         assert_eq!(timer.get_class(), ["work"]);
 
-        timer.current_index = 1;
+        timer.phase = Phase::ShortBreak;
         assert_eq!(timer.get_class(), ["break"]);
 
         timer.running = false;
@@ -235,7 +549,7 @@ mod tests {
         timer.times[2] = time;
 
         // Initial state
-        assert_eq!(timer.current_index, 0);
+        assert_eq!(timer.phase, Phase::Work);
         assert_eq!(timer.iterations, 0);
 
         // Update state after work time is completed
@@ -244,7 +558,7 @@ mod tests {
             std::thread::sleep(SLEEP_DURATION);
         }
         timer.update_state(&config);
-        assert_eq!(timer.current_index, 1); // Move to short break
+        assert_eq!(timer.phase, Phase::ShortBreak); // Move to short break
 
         // Update state after short break is completed
         for _ in 0..time * 1000 / SLEEP_TIME {
@@ -263,7 +577,362 @@ mod tests {
         }
 
         timer.update_state(&config);
-        assert_eq!(timer.current_index, 2); // Move to long break
+        assert_eq!(timer.phase, Phase::LongBreak); // Move to long break
+    }
+
+    #[test]
+    fn test_skip() {
+        let mut timer = create_timer();
+        let config = Config {
+            auto_short_break: true,
+            auto_long_break: true,
+            ..Default::default()
+        };
+
+        timer.elapsed_time = 42;
+        timer.skip(&config);
+        assert_eq!(timer.phase, Phase::ShortBreak);
+        assert_eq!(timer.elapsed_time, 0);
+        assert!(timer.running);
+
+        timer.skip(&config);
+        assert_eq!(timer.phase, Phase::Work);
+        assert_eq!(timer.iterations, 1);
+        assert!(!timer.running);
+
+        // skipping the last work cycle should still give us a long break
+        timer.iterations = config.intervals - 1;
+        timer.skip(&config);
+        assert_eq!(timer.phase, Phase::LongBreak);
+
+        timer.skip(&config);
+        assert_eq!(timer.phase, Phase::Work);
+        assert_eq!(timer.iterations, 0);
+        assert_eq!(timer.session_completed, 1);
+    }
+
+    #[test]
+    fn test_long_cycles() {
+        let mut timer = Timer::new(20 * HOUR, SHORT_BREAK_TIME, LONG_BREAK_TIME, 0);
+        let config = Config::default();
+
+        timer.elapsed_time = 20 * HOUR - 1;
+        timer.update_state(&config);
+        assert_eq!(timer.phase, Phase::Work);
+        assert_eq!(timer.get_remaining_time(), 1);
+
+        // the completed counter should keep counting past u8::MAX
+        timer.phase = Phase::LongBreak;
+        timer.iterations = config.intervals;
+        timer.session_completed = u8::MAX as u32;
+        timer.skip(&config);
+        assert_eq!(timer.session_completed, 256);
+    }
+
+    #[test]
+    fn test_sequence() {
+        let mut timer = create_timer();
+        let config = Config {
+            auto_work: true,
+            ..Default::default()
+        };
+        let mut deep = Step::new(Phase::Work, 90 * 60);
+        deep.class = Some("deep".to_string());
+
+        timer.set_sequence(vec![
+            Step::new(Phase::ShortBreak, 60),
+            deep,
+            Step::new(Phase::LongBreak, 30 * 60),
+        ]);
+        assert_eq!(timer.phase, Phase::ShortBreak);
+        assert_eq!(timer.get_current_time(), 60);
+
+        timer.skip(&config);
+        assert_eq!(timer.step, 1);
+        assert_eq!(timer.phase, Phase::Work);
+        assert_eq!(timer.get_current_time(), 90 * 60);
+        assert_eq!(timer.get_class(), ["work", "deep"]);
+        assert!(timer.running);
+
+        timer.skip(&config);
+        assert_eq!(timer.phase, Phase::LongBreak);
+        assert_eq!(timer.session_completed, 0);
+
+        timer.skip(&config);
+        assert_eq!(timer.step, 0);
+        assert_eq!(timer.phase, Phase::ShortBreak);
+        assert_eq!(timer.session_completed, 1);
+
+        timer.set_time(Phase::Work, 50 * 60);
+        assert_eq!(timer.sequence[1].duration, 50 * 60);
+        assert_eq!(timer.step, 0);
+    }
+
+    #[test]
+    fn test_auto_start() {
+        let mut timer = create_timer();
+        let config = Config {
+            auto_short_break: true,
+            auto_start_delay: 2,
+            ..Default::default()
+        };
+
+        timer.skip(&config);
+        assert_eq!(timer.phase, Phase::ShortBreak);
+        assert!(timer.running);
+        assert_eq!(timer.countdown, 2);
+        assert_eq!(timer.get_class(), ["break", "starting"]);
+
+        // the break only starts counting down once the delay is over
+        for _ in 0..3 * 1000 / SLEEP_TIME {
+            timer.increment_time();
+        }
+        assert_eq!(timer.countdown, 0);
+        assert_eq!(timer.elapsed_time, 1);
+
+        timer.skip(&config);
+        assert_eq!(timer.phase, Phase::Work);
+        assert!(!timer.running);
+        assert_eq!(timer.countdown, 0);
+
+        // long breaks have their own setting
+        timer.iterations = config.intervals - 1;
+        timer.skip(&config);
+        assert_eq!(timer.phase, Phase::LongBreak);
+        assert!(!timer.running);
+    }
+
+    #[test]
+    fn test_warning() {
+        let mut timer = create_timer();
+        let config = Config {
+            work_warning: 60,
+            ..Default::default()
+        };
+        timer.start();
+        timer.take_events();
+
+        timer.elapsed_time = WORK_TIME - 61;
+        timer.update_state(&config);
+        assert!(!timer.in_warning);
+
+        timer.elapsed_time = WORK_TIME - 60;
+        timer.update_state(&config);
+        timer.update_state(&config);
+        assert!(timer.in_warning);
+        assert_eq!(timer.get_class(), ["work", "warning"]);
+        assert_eq!(
+            timer.take_events(),
+            [Event::WarningStarted {
+                phase: Phase::Work,
+                remaining: 60
+            }]
+        );
+
+        // adding time ends the warning until it runs low again
+        timer.add_time(5 * 60);
+        timer.update_state(&config);
+        assert!(!timer.in_warning);
+
+        timer.elapsed_time = WORK_TIME + 5 * 60;
+        timer.update_state(&config);
+        assert_eq!(timer.phase, Phase::ShortBreak);
+        assert!(!timer.in_warning);
+
+        // short breaks have no warning set
+        timer.elapsed_time = SHORT_BREAK_TIME - 1;
+        timer.update_state(&config);
+        assert!(!timer.in_warning);
+    }
+
+    #[test]
+    fn test_flowtime() {
+        let mut timer = create_timer();
+        let config = Config {
+            flowtime: true,
+            flowtime_ratio: 0.2,
+            ..Default::default()
+        };
+
+        // work never runs out on its own
+        timer.elapsed_time = 50 * 60;
+        timer.update_state(&config);
+        assert_eq!(timer.phase, Phase::Work);
+        assert!(timer.counts_up(&config));
+
+        timer.take_break(&config);
+        assert_eq!(timer.phase, Phase::ShortBreak);
+        assert_eq!(timer.get_current_time(), 10 * 60);
+        assert!(timer.running);
+        assert_eq!(
+            timer.take_events()[0],
+            Event::PhaseCompleted {
+                phase: Phase::Work,
+                elapsed: 50 * 60,
+                planned: 50 * 60
+            }
+        );
+
+        // taking a break while on one does nothing
+        timer.take_break(&config);
+        assert_eq!(timer.get_current_time(), 10 * 60);
+
+        // the break counts down as usual, and the next one is earned again
+        timer.elapsed_time = 10 * 60;
+        timer.update_state(&config);
+        assert_eq!(timer.phase, Phase::Work);
+        assert_eq!(timer.earned_break, None);
+        assert_eq!(timer.iterations, 1);
+    }
+
+    #[test]
+    fn test_overtime() {
+        let mut timer = create_timer();
+        let config = Config {
+            overtime: true,
+            overtime_break: true,
+            ..Default::default()
+        };
+        timer.start();
+
+        timer.elapsed_time = WORK_TIME;
+        timer.update_state(&config);
+        timer.elapsed_time = WORK_TIME + 3 * 60;
+        timer.update_state(&config);
+        assert_eq!(timer.phase, Phase::Work);
+        assert!(timer.running);
+        assert_eq!(timer.get_overtime(), 3 * 60);
+        assert_eq!(timer.get_class(), ["work", "overtime"]);
+        assert_eq!(
+            timer.take_events(),
+            [
+                Event::Resumed,
+                Event::OvertimeStarted { phase: Phase::Work }
+            ]
+        );
+
+        // the overtime is added to the break that follows
+        timer.acknowledge(&config, true);
+        assert_eq!(timer.phase, Phase::ShortBreak);
+        assert!(!timer.in_overtime);
+        assert!(timer.running);
+        assert_eq!(timer.get_current_time(), SHORT_BREAK_TIME + 3 * 60);
+        assert_eq!(
+            timer.take_events()[0],
+            Event::PhaseCompleted {
+                phase: Phase::Work,
+                elapsed: WORK_TIME + 3 * 60,
+                planned: WORK_TIME
+            }
+        );
+    }
+
+    #[test]
+    fn test_overtime_auto_start() {
+        let mut timer = create_timer();
+        let config = Config {
+            overtime: true,
+            auto_short_break: true,
+            auto_long_break: true,
+            ..Default::default()
+        };
+
+        // an automatic break doesn't wait for anyone
+        timer.elapsed_time = WORK_TIME;
+        timer.update_state(&config);
+        assert_eq!(timer.phase, Phase::ShortBreak);
+
+        timer.elapsed_time = SHORT_BREAK_TIME;
+        timer.update_state(&config);
+        assert_eq!(timer.phase, Phase::ShortBreak);
+        assert!(timer.in_overtime);
+
+        timer.add_time(60);
+        assert!(!timer.in_overtime);
+    }
+
+    #[test]
+    fn test_away() {
+        let mut timer = create_timer();
+        let config = Config {
+            pause_on_lock: true,
+            resume_on_unlock: true,
+            idle_reset: 600,
+            ..Default::default()
+        };
+
+        timer.start();
+        timer.elapsed_time = 300;
+        timer.away(&config);
+        assert!(!timer.running);
+        timer.back(&config);
+        assert!(timer.running);
+        assert_eq!(timer.elapsed_time, 300);
+
+        // work left for longer than idle_reset starts over
+        timer.away(&config);
+        timer.away_since = Instant::now().checked_sub(std::time::Duration::from_secs(600));
+        timer.back(&config);
+        assert!(timer.running);
+        assert_eq!(timer.elapsed_time, 0);
+
+        // a timer that was already paused is left alone
+        timer.stop();
+        timer.elapsed_time = 300;
+        timer.away(&config);
+        timer.away_since = Instant::now().checked_sub(std::time::Duration::from_secs(600));
+        timer.back(&config);
+        assert!(!timer.running);
+        assert_eq!(timer.elapsed_time, 300);
+    }
+
+    #[test]
+    fn test_events() {
+        let mut timer = create_timer();
+        let config = Config {
+            auto_short_break: true,
+            auto_long_break: true,
+            ..Default::default()
+        };
+
+        timer.start();
+        timer.start();
+        timer.toggle();
+        timer.stop();
+        assert_eq!(timer.take_events(), [Event::Resumed, Event::Paused]);
+
+        timer.elapsed_time = 42;
+        timer.skip(&config);
+        assert_eq!(
+            timer.take_events(),
+            [
+                Event::Skipped {
+                    phase: Phase::Work,
+                    elapsed: 42,
+                    planned: WORK_TIME
+                },
+                Event::PhaseStarted {
+                    phase: Phase::ShortBreak
+                }
+            ]
+        );
+
+        timer.elapsed_time = SHORT_BREAK_TIME;
+        timer.update_state(&config);
+        timer.reset();
+        assert_eq!(
+            timer.take_events(),
+            [
+                Event::PhaseCompleted {
+                    phase: Phase::ShortBreak,
+                    elapsed: SHORT_BREAK_TIME,
+                    planned: SHORT_BREAK_TIME
+                },
+                Event::PhaseStarted { phase: Phase::Work },
+                Event::Reset
+            ]
+        );
+        assert!(timer.take_events().is_empty());
     }
 
     #[test]
@@ -0,0 +1,201 @@
+use std::{
+    collections::HashMap,
+    io::Write,
+    os::unix::net::UnixStream,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{models::config::Config, utils::helper::trim_whitespace};
+
+use super::{
+    events::{Event, Subscriber},
+    output::tooltip,
+    template::{placeholders, render},
+    timer::Timer,
+};
+
+// what a watcher is sent on every change
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Feed {
+    // the gist of the state, for scripts
+    State,
+    // everything a bar needs to print the module itself, see services::bar
+    Bar,
+}
+
+impl Feed {
+    fn line(&self, state: &Timer, config: &Config) -> String {
+        match self {
+            Feed::State => {
+                serde_json::to_string(&Snapshot::new(state)).expect("Not a serializable type")
+            }
+            Feed::Bar => json!({
+                "text": trim_whitespace(&render(&config.format, &placeholders(state, config))),
+                "tooltip": tooltip(state),
+                "class": state.get_class(),
+                "timer": state,
+            })
+            .to_string(),
+        }
+    }
+}
+
+// what's sent to watchers, as a line of json
+#[derive(Serialize)]
+struct Snapshot {
+    phase: &'static str,
+    remaining: u64,
+    running: bool,
+    completed: u32,
+}
+
+impl Snapshot {
+    fn new(state: &Timer) -> Self {
+        Self {
+            phase: state.phase.as_str(),
+            remaining: state.get_remaining_time(),
+            running: state.running,
+            completed: state.session_completed,
+        }
+    }
+}
+
+// clients that connected with the watch or bar operation, they get a line whenever it changes
+pub struct Watchers {
+    // handed over by the server as they connect
+    pending: Arc<Mutex<Vec<(UnixStream, Feed)>>>,
+    streams: Vec<(UnixStream, Feed)>,
+    last: HashMap<Feed, String>,
+}
+
+impl Watchers {
+    pub fn new(pending: Arc<Mutex<Vec<(UnixStream, Feed)>>>) -> Self {
+        Self {
+            pending,
+            streams: vec![],
+            last: HashMap::new(),
+        }
+    }
+}
+
+// false once the watcher is gone
+fn send(stream: &mut UnixStream, line: &str) -> bool {
+    writeln!(stream, "{line}").is_ok()
+}
+
+impl Subscriber for Watchers {
+    fn on_event(&mut self, _event: &Event, _state: &Timer, _config: &Config) {}
+
+    fn on_tick(&mut self, state: &Timer, config: &Config) {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        let streams = std::mem::take(&mut self.streams);
+
+        // only the feeds someone is watching
+        let mut lines = HashMap::new();
+        for (_, feed) in streams.iter().chain(&pending) {
+            lines
+                .entry(*feed)
+                .or_insert_with(|| feed.line(state, config));
+        }
+
+        for (mut stream, feed) in streams {
+            let line = &lines[&feed];
+            if self.last.get(&feed) == Some(line) || send(&mut stream, line) {
+                self.streams.push((stream, feed));
+            }
+        }
+        // new watchers start off with the current state
+        for (mut stream, feed) in pending {
+            // a watcher that stops reading mustn't hold up the timer
+            let _ = stream.set_write_timeout(Some(Duration::from_millis(100)));
+            if send(&mut stream, &lines[&feed]) {
+                self.streams.push((stream, feed));
+            }
+        }
+        self.last = lines;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader};
+
+    use serde_json::Value;
+
+    use super::*;
+    use crate::utils::consts::{LONG_BREAK_TIME, SHORT_BREAK_TIME, WORK_TIME};
+
+    #[test]
+    fn test_watchers() {
+        let pending = Arc::new(Mutex::new(vec![]));
+        let mut watchers = Watchers::new(pending.clone());
+        let config = Config::default();
+        let mut state = Timer::new(WORK_TIME, SHORT_BREAK_TIME, LONG_BREAK_TIME, 0);
+
+        let (server, client) = UnixStream::pair().unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_millis(100)))
+            .unwrap();
+        let mut client = BufReader::new(client);
+        pending.lock().unwrap().push((server, Feed::State));
+
+        let mut line = String::new();
+        watchers.on_tick(&state, &config);
+        client.read_line(&mut line).unwrap();
+        let snapshot: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(snapshot["phase"], "work");
+        assert_eq!(snapshot["remaining"], WORK_TIME);
+        assert_eq!(snapshot["running"], false);
+        assert_eq!(snapshot["completed"], 0);
+
+        // nothing changed, nothing sent
+        watchers.on_tick(&state, &config);
+        assert!(client.read_line(&mut String::new()).is_err());
+
+        state.running = true;
+        watchers.on_tick(&state, &config);
+        line.clear();
+        client.read_line(&mut line).unwrap();
+        let snapshot: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(snapshot["running"], true);
+
+        // watchers that left are dropped
+        drop(client);
+        state.running = false;
+        watchers.on_tick(&state, &config);
+        assert!(watchers.streams.is_empty());
+    }
+
+    #[test]
+    fn test_watchers_bar() {
+        let pending = Arc::new(Mutex::new(vec![]));
+        let mut watchers = Watchers::new(pending.clone());
+        let config = Config {
+            format: "{phase} {remaining}".to_string(),
+            ..Default::default()
+        };
+        let mut state = Timer::new(WORK_TIME, SHORT_BREAK_TIME, LONG_BREAK_TIME, 0);
+        state.session_completed = 1;
+
+        let (server, client) = UnixStream::pair().unwrap();
+        let mut client = BufReader::new(client);
+        pending.lock().unwrap().push((server, Feed::Bar));
+
+        let mut line = String::new();
+        watchers.on_tick(&state, &config);
+        client.read_line(&mut line).unwrap();
+        let frame: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(frame["text"], "work 25:00");
+        assert_eq!(frame["tooltip"], "1 pomodoro completed this session");
+        assert_eq!(frame["class"], serde_json::json!(state.get_class()));
+
+        // the bar gets the whole timer, to print it the same way the server would
+        let timer: Timer = serde_json::from_value(frame["timer"].clone()).unwrap();
+        assert_eq!(timer.session_completed, 1);
+        assert_eq!(timer.times, state.times);
+    }
+}
@@ -1,14 +1,22 @@
 use std::time::Duration;
 
-pub const SLEEP_TIME: u16 = 100;
-pub const SLEEP_DURATION: Duration = Duration::from_millis(SLEEP_TIME as u64);
-pub const MINUTE: u16 = 60;
-pub const HOUR: u16 = 60 * MINUTE;
+pub const SLEEP_TIME: u64 = 100;
+pub const SLEEP_DURATION: Duration = Duration::from_millis(SLEEP_TIME);
+pub const MINUTE: u64 = 60;
+pub const HOUR: u64 = 60 * MINUTE;
+pub const MAX_TIME: u64 = 7 * 24 * HOUR;
 pub const MAX_ITERATIONS: u8 = 4;
-pub const WORK_TIME: u16 = 25 * MINUTE;
-pub const SHORT_BREAK_TIME: u16 = 5 * MINUTE;
-pub const LONG_BREAK_TIME: u16 = 15 * MINUTE;
+pub const WORK_TIME: u64 = 25 * MINUTE;
+pub const SHORT_BREAK_TIME: u64 = 5 * MINUTE;
+pub const LONG_BREAK_TIME: u64 = 15 * MINUTE;
+pub const FLOWTIME_RATIO: f64 = 0.2;
+pub const HOOK_TIMEOUT: u64 = 10;
 pub const PLAY_ICON: &str = "▶";
 pub const PAUSE_ICON: &str = "⏸";
 pub const WORK_ICON: &str = "󰔟";
 pub const BREAK_ICON: &str = "";
+pub const WORK_COLOR: &str = "#E06C75";
+pub const BREAK_COLOR: &str = "#98C379";
+pub const PAUSE_COLOR: &str = "#ABB2BF";
+pub const SUMMARY: &str = "Pomodoro";
+pub const FORMAT: &str = "{state} {remaining} {icon}";
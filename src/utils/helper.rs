@@ -1,3 +1,7 @@
+use std::time::Duration;
+
+use super::consts::{HOUR, MAX_TIME, MINUTE};
+
 pub fn trim_whitespace(input: &str) -> String {
     let mut result = String::with_capacity(input.len());
     input.split_whitespace().for_each(|word| {
@@ -8,3 +12,203 @@ pub fn trim_whitespace(input: &str) -> String {
     });
     result
 }
+
+// parses durations such as "25m", "1h30m", "90s" or "0.5m"
+// a plain number without a unit is treated as minutes
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let trimmed = input.trim();
+    let trimmed = trimmed.strip_prefix('+').unwrap_or(trimmed);
+    if trimmed.is_empty() {
+        return Err("duration is empty".to_string());
+    }
+
+    if trimmed.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return to_duration(parse_number(trimmed, input)? * 60.0, input);
+    }
+
+    let mut total = 0.0;
+    let mut number = String::new();
+    for c in trimmed.chars() {
+        if c.is_whitespace() && number.is_empty() {
+            continue;
+        }
+        if c.is_ascii_digit() || c == '.' {
+            number.push(c);
+            continue;
+        }
+
+        let unit = match c {
+            'h' => 3600.0,
+            'm' => 60.0,
+            's' => 1.0,
+            _ => return Err(format!("invalid unit '{c}' in duration \"{input}\"")),
+        };
+        total += parse_number(&number, input)? * unit;
+        number.clear();
+    }
+
+    if !number.is_empty() {
+        return Err(format!(
+            "missing unit after {number} in duration \"{input}\""
+        ));
+    }
+
+    to_duration(total, input)
+}
+
+// parses a cycle time and returns it in whole seconds
+pub fn parse_time(input: &str) -> Result<u64, String> {
+    let seconds = parse_duration(input)?.as_secs();
+    if seconds == 0 {
+        return Err(format!("duration \"{input}\" must be at least one second"));
+    }
+    if seconds > MAX_TIME {
+        return Err(format!(
+            "duration \"{input}\" is too long, the maximum is {}h",
+            MAX_TIME / HOUR
+        ));
+    }
+    Ok(seconds)
+}
+
+// parses how long to wait before something happens, unlike cycle times this can be zero
+pub fn parse_delay(input: &str) -> Result<u64, String> {
+    let seconds = parse_duration(input)?.as_secs();
+    if seconds > MAX_TIME {
+        return Err(format!(
+            "duration \"{input}\" is too long, the maximum is {}h",
+            MAX_TIME / HOUR
+        ));
+    }
+    Ok(seconds)
+}
+
+// the reverse of parse_duration, e.g. 5400 => "1h30m"
+pub fn format_duration(seconds: u64) -> String {
+    let parts = [
+        (seconds / HOUR, "h"),
+        ((seconds % HOUR) / MINUTE, "m"),
+        (seconds % MINUTE, "s"),
+    ];
+
+    let result = parts
+        .iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{value}{unit}"))
+        .collect::<String>();
+    if result.is_empty() {
+        return "0s".to_string();
+    }
+    result
+}
+
+// parses a fraction such as "1/5" or "0.2", it has to be above 0 and at most 1
+pub fn parse_ratio(input: &str) -> Result<f64, String> {
+    let invalid = || format!("invalid ratio \"{input}\", expected e.g. 1/5 or 0.2");
+    let ratio = match input.trim().split_once('/') {
+        Some((numerator, denominator)) => {
+            let numerator = numerator.trim().parse::<f64>().map_err(|_| invalid())?;
+            let denominator = denominator.trim().parse::<f64>().map_err(|_| invalid())?;
+            numerator / denominator
+        }
+        None => input.trim().parse::<f64>().map_err(|_| invalid())?,
+    };
+
+    if !(ratio > 0.0 && ratio <= 1.0) {
+        return Err(format!("ratio \"{input}\" must be above 0 and at most 1"));
+    }
+    Ok(ratio)
+}
+
+fn parse_number(number: &str, input: &str) -> Result<f64, String> {
+    number
+        .parse::<f64>()
+        .map_err(|_| format!("invalid number in duration \"{input}\""))
+}
+
+fn to_duration(seconds: f64, input: &str) -> Result<Duration, String> {
+    Duration::try_from_secs_f64(seconds).map_err(|_| format!("invalid duration \"{input}\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("25m"), Ok(Duration::from_secs(25 * 60)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(90 * 60)));
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("0.5m"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("+5m"), Ok(Duration::from_secs(5 * 60)));
+        assert_eq!(parse_duration("1h 5s"), Ok(Duration::from_secs(3605)));
+    }
+
+    #[test]
+    fn test_parse_duration_plain_number() {
+        assert_eq!(parse_duration("25"), Ok(Duration::from_secs(25 * 60)));
+        assert_eq!(parse_duration("1.5"), Ok(Duration::from_secs(90)));
+    }
+
+    #[test]
+    fn test_parse_duration_invalid() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("abc").is_err());
+        assert!(parse_duration("-5m").is_err());
+        assert!(parse_duration("5x").is_err());
+        assert!(parse_duration("5 m").is_err());
+        assert!(parse_duration("1..5m").is_err());
+        assert_eq!(
+            parse_duration("1h30"),
+            Err("missing unit after 30 in duration \"1h30\"".to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(25 * 60), "25m");
+        assert_eq!(format_duration(90 * 60), "1h30m");
+        assert_eq!(format_duration(90), "1m30s");
+        assert_eq!(format_duration(3600 + 5), "1h5s");
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(
+            parse_duration(&format_duration(12345)),
+            Ok(Duration::from_secs(12345))
+        );
+    }
+
+    #[test]
+    fn test_parse_delay() {
+        assert_eq!(parse_delay("10s"), Ok(10));
+        assert_eq!(parse_delay("0"), Ok(0));
+        assert!(parse_delay("169h").is_err());
+        assert!(parse_delay("soon").is_err());
+    }
+
+    #[test]
+    fn test_parse_ratio() {
+        assert_eq!(parse_ratio("1/5"), Ok(0.2));
+        assert_eq!(parse_ratio("0.25"), Ok(0.25));
+        assert_eq!(parse_ratio(" 1 / 2 "), Ok(0.5));
+        assert_eq!(parse_ratio("1"), Ok(1.0));
+        assert!(parse_ratio("0").is_err());
+        assert!(parse_ratio("1/0").is_err());
+        assert!(parse_ratio("3/2").is_err());
+        assert!(parse_ratio("fifth").is_err());
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("25m"), Ok(25 * 60));
+        assert_eq!(parse_time("90s"), Ok(90));
+        assert!(parse_time("0").is_err());
+        assert!(parse_time("0.5s").is_err());
+        assert_eq!(parse_time("24h"), Ok(24 * 60 * 60));
+        assert_eq!(parse_time("168h"), Ok(MAX_TIME));
+        assert_eq!(
+            parse_time("169h"),
+            Err("duration \"169h\" is too long, the maximum is 168h".to_string())
+        );
+        assert!(parse_time("99999999999999999999h").is_err());
+    }
+}